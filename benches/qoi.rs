@@ -0,0 +1,68 @@
+//! Compares QOI and PNG encoding and decoding throughput on a typical simulation frame.
+//!
+//! Run with `cargo bench --features png --bench qoi`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+use verle::colors::{GRAY, GREEN, RED};
+use verle::{Canvas, Pos, Size};
+
+const ITERATIONS: u32 = 20;
+
+fn frame() -> Canvas {
+    let size = Size::new(1920, 1080);
+    let mut canvas = Canvas::new(GRAY, size);
+    for i in 0..2000 {
+        let x = (i * 7919) % size.width as i32;
+        let y = (i * 104_729) % size.height as i32;
+        canvas.draw_circle(Pos::new(x, y), (i % 12) as u32 + 2, RED, GREEN);
+    }
+    canvas.draw_line(Pos::new(0, 0), Pos::new(1919, 1079), RED);
+    canvas
+}
+
+fn bench(name: &str, pixels: usize, mut f: impl FnMut()) {
+    f();
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    let elapsed = start.elapsed() / ITERATIONS;
+    println!(
+        "{name:<12} {:>10.3} ms/frame {:>10.1} Mpx/s",
+        elapsed.as_secs_f64() * 1000.0,
+        pixels as f64 / elapsed.max(Duration::from_nanos(1)).as_secs_f64() / 1e6
+    );
+}
+
+fn main() {
+    let canvas = frame();
+    let pixels = canvas.pixels().len();
+
+    let mut qoi = vec![];
+    canvas.write_qoi(&mut qoi).unwrap();
+    let mut png = vec![];
+    canvas.write_png(&mut png).unwrap();
+    println!(
+        "qoi size: {} bytes, png size: {} bytes",
+        qoi.len(),
+        png.len()
+    );
+
+    bench("qoi encode", pixels, || {
+        let mut data = Vec::with_capacity(qoi.len());
+        black_box(&canvas).write_qoi(&mut data).unwrap();
+        black_box(data);
+    });
+    bench("qoi decode", pixels, || {
+        black_box(Canvas::read_qoi(black_box(qoi.as_slice())).unwrap());
+    });
+    bench("png encode", pixels, || {
+        let mut data = Vec::with_capacity(png.len());
+        black_box(&canvas).write_png(&mut data).unwrap();
+        black_box(data);
+    });
+    bench("png decode", pixels, || {
+        black_box(Canvas::read_png(black_box(png.as_slice())).unwrap());
+    });
+}
@@ -1,45 +1,131 @@
+mod batch;
+mod tiled;
+
+pub use batch::{BatchOptions, Circle, CircleBatch};
+pub use tiled::{TileOptions, TiledRenderer};
+
+use crate::draw::Draw;
+use crate::filter::Filter;
+use crate::font::Font;
 use crate::Size;
-use crate::{Pos, Rect};
-use ndarray::{Array, Array2};
-use softbuffer::Buffer;
+use crate::{Pos, Rect, Region};
+use glam::{Affine2, Vec2};
+use ndarray::{s, Array, Array2, ArrayViewMut2};
+use std::ops::{Range, RangeInclusive};
 use thiserror::Error;
-use winit::raw_window_handle::{HasDisplayHandle, HasWindowHandle};
+
+/// Damaged rects kept apart before they are merged into their bounding rect.
+const DAMAGE_RECTS_MAX: usize = 16;
 
 pub struct Canvas {
     buffer: Array2<u32>,
     global_rect: Rect,
+    damage: Vec<Rect>,
 }
 
 impl Canvas {
+    /// Panics if a dimension exceeds `i32::MAX`.
     #[inline]
     pub fn new(default_color: u32, size: Size) -> Self {
+        Self::from_pixels(Array::from_elem(
+            (size.height as usize, size.width as usize),
+            default_color,
+        ))
+    }
+
+    /// Panics if a dimension exceeds `i32::MAX`.
+    #[inline]
+    pub fn from_pixels(pixels: Array2<u32>) -> Self {
+        let (height, width) = pixels.dim();
+        let size = Size::new(
+            u32::try_from(width).unwrap_or(u32::MAX),
+            u32::try_from(height).unwrap_or(u32::MAX),
+        );
+        let global_rect = Rect::try_new_size(Pos::new(0, 0), size)
+            .unwrap_or_else(|err| panic!("invalid canvas size: {err}"));
         Self {
-            buffer: Array::from_elem((size.height as usize, size.width as usize), default_color),
-            global_rect: Rect::try_new_size(Pos::new(0, 0), size)
-                .unwrap_or_else(|_| unreachable!(/*valid until top_left is not negative*/)),
+            buffer: pixels,
+            global_rect,
+            damage: [global_rect]
+                .into_iter()
+                .filter(|rect| !rect.is_zero_size())
+                .collect(),
         }
     }
 
     #[inline]
-    pub fn flush<D, W>(&self, buffer: &mut Buffer<'_, D, W>) -> Result<(), Error>
-    where
-        D: HasDisplayHandle,
-        W: HasWindowHandle,
-    {
-        buffer.copy_from_slice(self.buffer.as_slice().ok_or(Error::InvalidBufferLayout)?);
-        Ok(())
+    pub fn size(&self) -> Size {
+        self.global_rect.size()
+    }
+
+    #[inline]
+    pub fn rect(&self) -> Rect {
+        self.global_rect
+    }
+
+    #[inline]
+    pub fn clear(&mut self, color: u32) {
+        self.buffer.fill(color);
+        self.add_damage(Some(self.global_rect));
+    }
+
+    #[inline]
+    pub fn pixels(&self) -> &Array2<u32> {
+        &self.buffer
+    }
+
+    /// Rects changed since the canvas was created or the damage was last cleared. Together they
+    /// cover every changed pixel; nearby rects are merged into their bounding rect, so they may
+    /// also cover some unchanged ones.
+    #[inline]
+    pub fn damage(&self) -> &[Rect] {
+        &self.damage
+    }
+
+    /// Forgets the damage, typically once the canvas has been presented.
+    #[inline]
+    pub fn clear_damage(&mut self) {
+        self.damage.clear();
+    }
+
+    /// Adds `rect` to the damage, merged with every damaged rect whose bounding rect with it is no
+    /// larger than both rects together.
+    fn add_damage(&mut self, rect: Option<Rect>) {
+        let Some(mut rect) = rect.filter(|rect| !rect.is_zero_size()) else {
+            return;
+        };
+        while let Some(index) = self
+            .damage
+            .iter()
+            .position(|damaged| damaged.union(&rect).area() <= damaged.area() + rect.area())
+        {
+            rect = rect.union(&self.damage.swap_remove(index));
+        }
+        self.damage.push(rect);
+        if self.damage.len() > DAMAGE_RECTS_MAX {
+            let bounds = self
+                .damage
+                .iter()
+                .fold(rect, |bounds, damaged| bounds.union(damaged));
+            self.damage.clear();
+            self.damage.push(bounds);
+        }
     }
 
     #[inline]
     pub fn draw_circle(&mut self, center: Pos, radius: u32, outline_color: u32, fill_color: u32) {
         Self::draw_dot_or_circle_in_rect_inner(
-            &mut self.buffer,
+            &mut Band::new(self.buffer.view_mut(), 0),
             center,
             radius,
             outline_color,
             fill_color,
             &self.global_rect,
         );
+        self.add_damage(clipped_bounds(
+            Self::circle_bounds(center, radius),
+            &self.global_rect,
+        ));
     }
 
     #[inline]
@@ -53,19 +139,41 @@ impl Canvas {
     ) {
         if let Some(rect) = &self.global_rect & rect {
             Self::draw_dot_or_circle_in_rect_inner(
-                &mut self.buffer,
+                &mut Band::new(self.buffer.view_mut(), 0),
                 center,
                 radius,
                 outline_color,
                 fill_color,
                 &rect,
             );
+            self.add_damage(clipped_bounds(Self::circle_bounds(center, radius), &rect));
+        }
+    }
+
+    #[inline]
+    pub fn draw_circle_in_region(
+        &mut self,
+        center: Pos,
+        radius: u32,
+        outline_color: u32,
+        fill_color: u32,
+        region: &Region,
+    ) {
+        for rect in region {
+            self.draw_circle_in_rect(center, radius, outline_color, fill_color, rect);
         }
     }
 
+    /// Left, top, right and bottom pixel a circle may cover.
+    #[inline]
+    fn circle_bounds(center: Pos, radius: u32) -> [i64; 4] {
+        let (x, y, radius) = (i64::from(center.x), i64::from(center.y), i64::from(radius));
+        [x - radius, y - radius, x + radius, y + radius]
+    }
+
     #[inline]
     fn draw_dot_or_circle_in_rect_inner(
-        buffer: &mut Array2<u32>,
+        buffer: &mut Band,
         center: Pos,
         radius: u32,
         outline_color: u32,
@@ -89,8 +197,12 @@ impl Canvas {
         }
     }
 
+    /// Fills the circle row by row: pixels at a squared distance below `r² + r` from the center
+    /// are drawn, those within `r² - r` with the fill color. The half widths of both spans only
+    /// shrink away from the center row, so they are tracked incrementally like in the midpoint
+    /// circle algorithm.
     fn draw_circle_in_rect_inner(
-        buffer: &mut Array2<u32>,
+        buffer: &mut Band,
         center: Pos,
         radius: u32,
         outline_color: u32,
@@ -100,87 +212,642 @@ impl Canvas {
         debug_assert!(radius != 0);
         debug_assert!(!rect.is_zero_size());
 
-        let radius_i = radius as i32;
-        let r2 = radius * radius;
-
-        let clamp_x = |x: i32| x.clamp(rect.left(), rect.right() - 1);
-        let x_range = (clamp_x(center.x - radius_i), clamp_x(center.x + radius_i));
+        let Some(rows) = Self::circle_rows(center, radius, rect) else {
+            return;
+        };
+        let radius = u64::from(radius);
+        let outline_limit = radius * radius + radius - 1;
+        let fill_limit = radius * radius - radius;
+        let half_width = |limit: u64, dy: u64| limit.checked_sub(dy * dy).map(u64::isqrt);
+        let mut outline = half_width(outline_limit, *rows.start());
+        let mut fill = half_width(fill_limit, *rows.start());
+        for dy in rows {
+            let shrink = |width: &mut Option<u64>, limit: u64| {
+                while let Some(w) = *width {
+                    if w * w + dy * dy <= limit {
+                        break;
+                    }
+                    *width = w.checked_sub(1);
+                }
+            };
+            shrink(&mut outline, outline_limit);
+            shrink(&mut fill, fill_limit);
+            let Some(outline) = outline else {
+                break;
+            };
+            Self::draw_circle_rows_inner(
+                buffer,
+                center,
+                dy,
+                (outline, fill),
+                (outline_color, fill_color),
+                rect,
+            );
+        }
+    }
 
-        let clamp_y = |y: i32| y.clamp(rect.top(), rect.bottom() - 1);
-        let y_range = (clamp_y(center.y - radius_i), clamp_y(center.y + radius_i));
+    /// Distances from the center row of the circle rows inside `rect`, if any.
+    #[inline]
+    fn circle_rows(center: Pos, radius: u32, rect: &Rect) -> Option<RangeInclusive<u64>> {
+        let (top, bottom) = (i64::from(rect.top()), i64::from(rect.bottom()) - 1);
+        let center_y = i64::from(center.y);
+        let first = (top - center_y).max(center_y - bottom).max(0) as u64;
+        let last = (bottom - center_y).max(center_y - top) as u64;
+        (first <= u64::from(radius)).then(|| first..=last.min(u64::from(radius)))
+    }
 
-        for x in x_range.0..=x_range.1 {
-            let offset_x = x - center.x;
-            let offset_x_2 = (offset_x * offset_x) as u32;
-            for y in y_range.0..=y_range.1 {
-                let offset_y = y - center.y;
-                let dist = offset_x_2 + (offset_y * offset_y) as u32;
-                if dist < r2 + radius {
-                    buffer[(y as usize, x as usize)] = if dist <= r2 - radius {
-                        fill_color
-                    } else {
-                        outline_color
-                    }
+    /// Draws the rows `dy` above and below the center of a circle, whose outline and fill reach
+    /// the given half widths left and right of the center.
+    fn draw_circle_rows_inner(
+        buffer: &mut Band,
+        center: Pos,
+        dy: u64,
+        (outline, fill): (u64, Option<u64>),
+        (outline_color, fill_color): (u32, u32),
+        rect: &Rect,
+    ) {
+        let (left, right) = (i64::from(rect.left()), i64::from(rect.right()) - 1);
+        let (center_x, center_y) = (i64::from(center.x), i64::from(center.y));
+        let mut fill_span = |y: i64, from: i64, to: i64, color: u32| {
+            let (from, to) = (from.max(left), to.min(right));
+            if from <= to {
+                buffer.fill(
+                    y as usize..y as usize + 1,
+                    from as usize..to as usize + 1,
+                    color,
+                );
+            }
+        };
+        let outline = outline as i64;
+        let rows = [center_y - dy as i64, center_y + dy as i64];
+        for y in rows.into_iter().take(if dy == 0 { 1 } else { 2 }) {
+            if y < i64::from(rect.top()) || y >= i64::from(rect.bottom()) {
+                continue;
+            }
+            match fill {
+                Some(fill) => {
+                    let fill = fill as i64;
+                    fill_span(y, center_x - outline, center_x - fill - 1, outline_color);
+                    fill_span(y, center_x - fill, center_x + fill, fill_color);
+                    fill_span(y, center_x + fill + 1, center_x + outline, outline_color);
                 }
+                None => fill_span(y, center_x - outline, center_x + outline, outline_color),
             }
         }
     }
 
     #[inline]
     pub fn draw_dot(&mut self, pos: Pos, color: u32) {
-        Self::draw_dot_in_rect_inner(&mut self.buffer, pos, color, &self.global_rect);
+        Self::draw_dot_in_rect_inner(
+            &mut Band::new(self.buffer.view_mut(), 0),
+            pos,
+            color,
+            &self.global_rect,
+        );
+        self.add_damage(clipped_bounds(
+            Self::circle_bounds(pos, 0),
+            &self.global_rect,
+        ));
     }
 
     #[inline]
     pub fn draw_dot_in_rect(&mut self, pos: Pos, color: u32, rect: &Rect) {
         if let Some(rect) = &self.global_rect & rect {
-            Self::draw_dot_in_rect_inner(&mut self.buffer, pos, color, &rect);
+            Self::draw_dot_in_rect_inner(
+                &mut Band::new(self.buffer.view_mut(), 0),
+                pos,
+                color,
+                &rect,
+            );
+            self.add_damage(clipped_bounds(Self::circle_bounds(pos, 0), &rect));
         }
     }
 
     #[inline]
-    fn draw_dot_in_rect_inner(buffer: &mut Array2<u32>, pos: Pos, color: u32, rect: &Rect) {
+    pub fn draw_dot_in_region(&mut self, pos: Pos, color: u32, region: &Region) {
+        for rect in region {
+            self.draw_dot_in_rect(pos, color, rect);
+        }
+    }
+
+    #[inline]
+    fn draw_dot_in_rect_inner(buffer: &mut Band, pos: Pos, color: u32, rect: &Rect) {
         if rect.contains(pos) {
-            buffer[(pos.y as usize, pos.x as usize)] = color;
+            buffer.set(pos, color);
         }
     }
 
     #[inline]
     pub fn draw_line(&mut self, from: Pos, to: Pos, color: u32) {
-        Self::draw_line_in_rect_inner(&mut self.buffer, from, to, color, &self.global_rect);
+        Self::draw_line_in_rect_inner(
+            &mut Band::new(self.buffer.view_mut(), 0),
+            from,
+            to,
+            color,
+            &self.global_rect,
+        );
+        self.add_damage(clipped_bounds(
+            Self::line_bounds(from, to),
+            &self.global_rect,
+        ));
     }
 
     #[inline]
     pub fn draw_line_in_rect(&mut self, from: Pos, to: Pos, color: u32, rect: &Rect) {
         if let Some(rect) = &self.global_rect & rect {
-            Self::draw_line_in_rect_inner(&mut self.buffer, from, to, color, &rect);
+            Self::draw_line_in_rect_inner(
+                &mut Band::new(self.buffer.view_mut(), 0),
+                from,
+                to,
+                color,
+                &rect,
+            );
+            self.add_damage(clipped_bounds(Self::line_bounds(from, to), &rect));
+        }
+    }
+
+    #[inline]
+    pub fn draw_line_in_region(&mut self, from: Pos, to: Pos, color: u32, region: &Region) {
+        for rect in region {
+            self.draw_line_in_rect(from, to, color, rect);
+        }
+    }
+
+    #[inline]
+    fn line_bounds(from: Pos, to: Pos) -> [i64; 4] {
+        [
+            i64::from(from.x.min(to.x)),
+            i64::from(from.y.min(to.y)),
+            i64::from(from.x.max(to.x)),
+            i64::from(from.y.max(to.y)),
+        ]
+    }
+
+    /// Draws the pixels of the Bresenham line from `from` to `to` that lie in `rect`.
+    ///
+    /// Along the major axis the walk visits every offset `i` in `0..=major` exactly once, at the
+    /// minor offset `(2 * minor * i + major) / (2 * major)`, i.e. `i * minor / major` rounded half
+    /// up. That gives the range of `i` inside `rect` directly, so the parts outside are skipped.
+    fn draw_line_in_rect_inner(buffer: &mut Band, from: Pos, to: Pos, color: u32, rect: &Rect) {
+        if rect.is_zero_size() {
+            return;
+        }
+        let (dx, dy) = (
+            i64::from(from.x.abs_diff(to.x)),
+            i64::from(from.y.abs_diff(to.y)),
+        );
+        let sx: i64 = if from.x < to.x { 1 } else { -1 };
+        let sy: i64 = if from.y < to.y { 1 } else { -1 };
+        let (Some(x_range), Some(y_range)) = (
+            Self::line_offsets_in(from.x, sx, rect.left(), rect.right(), dx),
+            Self::line_offsets_in(from.y, sy, rect.top(), rect.bottom(), dy),
+        ) else {
+            return;
+        };
+
+        let (major, minor, major_range, (minor_first, minor_last)) = if dx >= dy {
+            (dx, dy, x_range, y_range)
+        } else {
+            (dy, dx, y_range, x_range)
+        };
+        let (mut first, mut last) = major_range;
+        if minor > 0 {
+            // solve for the major offsets whose minor offset lies in the minor range, `below` is
+            // the largest major offset with a smaller minor offset than `minor_offset`
+            let (major, minor) = (i128::from(major), i128::from(minor));
+            let below = |minor_offset: i64| {
+                (2 * major * i128::from(minor_offset) - major - 1).div_euclid(2 * minor)
+            };
+            first = i128::from(first).max(below(minor_first) + 1) as i64;
+            last = i128::from(last).min(below(minor_last + 1)) as i64;
         }
+        if first > last {
+            return;
+        }
+
+        let start = 2 * i128::from(minor) * i128::from(first) + i128::from(major);
+        let period = i128::from(2 * major).max(1);
+        let mut minor_offset = (start / period) as i64;
+        let mut offset = (start % period) as i64;
+        for major_offset in first..=last {
+            let (x, y) = if dx >= dy {
+                (major_offset, minor_offset)
+            } else {
+                (minor_offset, major_offset)
+            };
+            let pos = Pos::new(
+                (i64::from(from.x) + sx * x) as i32,
+                (i64::from(from.y) + sy * y) as i32,
+            );
+            debug_assert!(rect.contains(pos));
+            buffer.set(pos, color);
+            offset += 2 * minor;
+            if offset >= 2 * major {
+                offset -= 2 * major;
+                minor_offset += 1;
+            }
+        }
+    }
+
+    /// Inclusive range of the offsets in `0..=len` from `start` towards `step` that land in
+    /// `low..high`.
+    #[inline]
+    fn line_offsets_in(start: i32, step: i64, low: i32, high: i32, len: i64) -> Option<(i64, i64)> {
+        let (start, low, high) = (i64::from(start), i64::from(low), i64::from(high) - 1);
+        let (first, last) = if step > 0 {
+            (low - start, high - start)
+        } else {
+            (start - high, start - low)
+        };
+        let (first, last) = (first.max(0), last.min(len));
+        (first <= last).then_some((first, last))
     }
 
-    fn draw_line_in_rect_inner(
+    /// Draws `image` mapped by `transform`, which takes image pixel space into canvas pixel
+    /// space. Every covered canvas pixel is sampled at its center with `filter`.
+    #[inline]
+    pub fn draw_image(&mut self, image: &Canvas, transform: Affine2, filter: Filter) {
+        let damage = Self::draw_image_in_rect_inner(
+            &mut self.buffer,
+            &image.buffer,
+            transform,
+            filter,
+            &self.global_rect,
+        );
+        self.add_damage(damage);
+    }
+
+    #[inline]
+    pub fn draw_image_in_rect(
+        &mut self,
+        image: &Canvas,
+        transform: Affine2,
+        filter: Filter,
+        rect: &Rect,
+    ) {
+        if let Some(rect) = &self.global_rect & rect {
+            let damage = Self::draw_image_in_rect_inner(
+                &mut self.buffer,
+                &image.buffer,
+                transform,
+                filter,
+                &rect,
+            );
+            self.add_damage(damage);
+        }
+    }
+
+    #[inline]
+    pub fn draw_image_in_region(
+        &mut self,
+        image: &Canvas,
+        transform: Affine2,
+        filter: Filter,
+        region: &Region,
+    ) {
+        for rect in region {
+            self.draw_image_in_rect(image, transform, filter, rect);
+        }
+    }
+
+    /// Returns the rect of the canvas pixels the image may have been drawn to.
+    fn draw_image_in_rect_inner(
         buffer: &mut Array2<u32>,
-        from: Pos,
-        to: Pos,
+        image: &Array2<u32>,
+        transform: Affine2,
+        filter: Filter,
+        rect: &Rect,
+    ) -> Option<Rect> {
+        if rect.is_zero_size() || image.is_empty() || transform.matrix2.determinant() == 0.0 {
+            return None;
+        }
+        let inverse = transform.inverse();
+        if !inverse.is_finite() {
+            return None;
+        }
+
+        let (height, width) = image.dim();
+        let corners = [
+            Vec2::ZERO,
+            Vec2::new(width as f32, 0.0),
+            Vec2::new(0.0, height as f32),
+            Vec2::new(width as f32, height as f32),
+        ]
+        .map(|corner| transform.transform_point2(corner));
+        let min = corners
+            .iter()
+            .copied()
+            .reduce(Vec2::min)
+            .unwrap_or_default();
+        let max = corners
+            .iter()
+            .copied()
+            .reduce(Vec2::max)
+            .unwrap_or_default();
+        let bounds = Rect::try_new(
+            Pos::new(min.x.floor() as i32, min.y.floor() as i32),
+            Pos::new(max.x.ceil() as i32, max.y.ceil() as i32),
+        )
+        .ok()
+        .and_then(|bounds| &bounds & rect)?;
+
+        for y in bounds.top()..bounds.bottom() {
+            for x in bounds.left()..bounds.right() {
+                let src = inverse.transform_point2(Vec2::new(x as f32 + 0.5, y as f32 + 0.5));
+                if let Some(color) = filter.sample(image, src) {
+                    buffer[(y as usize, x as usize)] = color;
+                }
+            }
+        }
+        Some(bounds)
+    }
+
+    /// Draws `text` with its top left corner at `pos`, every font pixel as a `scale` x `scale`
+    /// block. Lines are separated by `\n`, see [`Font::measure_text`] for the covered size.
+    #[inline]
+    pub fn draw_text(&mut self, pos: Pos, text: &str, font: &Font, color: u32, scale: u32) {
+        let damage = Self::draw_text_in_rect_inner(
+            &mut Band::new(self.buffer.view_mut(), 0),
+            pos,
+            text,
+            font,
+            color,
+            scale,
+            &self.global_rect,
+        );
+        self.add_damage(damage);
+    }
+
+    #[inline]
+    pub fn draw_text_in_rect(
+        &mut self,
+        pos: Pos,
+        text: &str,
+        font: &Font,
         color: u32,
+        scale: u32,
         rect: &Rect,
     ) {
-        if rect.is_zero_size()
-            || (from.x < rect.left() && to.x < rect.left())
-            || (from.x >= rect.right() && to.x >= rect.right())
-            || (from.y < rect.top() && to.y < rect.top())
-            || (from.y >= rect.bottom() && to.y >= rect.bottom())
-        {
-            return;
+        if let Some(rect) = &self.global_rect & rect {
+            let damage = Self::draw_text_in_rect_inner(
+                &mut Band::new(self.buffer.view_mut(), 0),
+                pos,
+                text,
+                font,
+                color,
+                scale,
+                &rect,
+            );
+            self.add_damage(damage);
+        }
+    }
+
+    #[inline]
+    pub fn draw_text_in_region(
+        &mut self,
+        pos: Pos,
+        text: &str,
+        font: &Font,
+        color: u32,
+        scale: u32,
+        region: &Region,
+    ) {
+        for rect in region {
+            self.draw_text_in_rect(pos, text, font, color, scale, rect);
         }
+    }
+
+    /// Returns the bounding rect of the drawn pixels.
+    fn draw_text_in_rect_inner(
+        buffer: &mut Band,
+        pos: Pos,
+        text: &str,
+        font: &Font,
+        color: u32,
+        scale: u32,
+        rect: &Rect,
+    ) -> Option<Rect> {
+        if rect.is_zero_size() || scale == 0 {
+            return None;
+        }
+        // pen positions of long texts may leave the i32 range even though they are clipped
+        let scale = i64::from(scale);
+        let (left, top, right, bottom) = (
+            i64::from(rect.left()),
+            i64::from(rect.top()),
+            i64::from(rect.right()),
+            i64::from(rect.bottom()),
+        );
+        let line_height = i64::from(font.line_height()) * scale;
+        let mut bounds: Option<[i64; 4]> = None;
+        for (pen_x, pen_y, glyph) in font.layout(text) {
+            let glyph_left = i64::from(pos.x).saturating_add(pen_x.saturating_mul(scale));
+            let glyph_top = i64::from(pos.y).saturating_add(pen_y.saturating_mul(scale));
+            if glyph_top >= bottom || glyph_top + line_height <= top || glyph_left >= right {
+                continue;
+            }
+            for ((y, x), _) in font
+                .glyph_pixels(glyph)
+                .indexed_iter()
+                .filter(|(_, &set)| set)
+            {
+                let block_left = glyph_left + x as i64 * scale;
+                let block_top = glyph_top + y as i64 * scale;
+                let (x0, x1) = (block_left.max(left), (block_left + scale).min(right));
+                let (y0, y1) = (block_top.max(top), (block_top + scale).min(bottom));
+                if x0 < x1 && y0 < y1 {
+                    buffer.fill(y0 as usize..y1 as usize, x0 as usize..x1 as usize, color);
+                    let [left, top, right, bottom] = bounds.get_or_insert([x0, y0, x1, y1]);
+                    (*left, *top) = ((*left).min(x0), (*top).min(y0));
+                    (*right, *bottom) = ((*right).max(x1), (*bottom).max(y1));
+                }
+            }
+        }
+        bounds.and_then(|[left, top, right, bottom]| {
+            clipped_bounds([left, top, right - 1, bottom - 1], rect)
+        })
+    }
+}
+
+impl Draw for Canvas {
+    #[inline]
+    fn size(&self) -> Size {
+        Canvas::size(self)
+    }
+
+    #[inline]
+    fn draw_circle_in_rect(
+        &mut self,
+        center: Pos,
+        radius: u32,
+        outline_color: u32,
+        fill_color: u32,
+        rect: &Rect,
+    ) {
+        Canvas::draw_circle_in_rect(self, center, radius, outline_color, fill_color, rect);
+    }
+
+    #[inline]
+    fn draw_dot_in_rect(&mut self, pos: Pos, color: u32, rect: &Rect) {
+        Canvas::draw_dot_in_rect(self, pos, color, rect);
+    }
+
+    #[inline]
+    fn draw_line_in_rect(&mut self, from: Pos, to: Pos, color: u32, rect: &Rect) {
+        Canvas::draw_line_in_rect(self, from, to, color, rect);
+    }
+
+    #[inline]
+    fn draw_circle(&mut self, center: Pos, radius: u32, outline_color: u32, fill_color: u32) {
+        Canvas::draw_circle(self, center, radius, outline_color, fill_color);
+    }
+
+    #[inline]
+    fn draw_dot(&mut self, pos: Pos, color: u32) {
+        Canvas::draw_dot(self, pos, color);
+    }
+
+    #[inline]
+    fn draw_line(&mut self, from: Pos, to: Pos, color: u32) {
+        Canvas::draw_line(self, from, to, color);
+    }
+}
+
+/// The pixels `left..=right` and `top..=bottom` of `bounds` that lie in `rect`, if any.
+#[inline]
+fn clipped_bounds([left, top, right, bottom]: [i64; 4], rect: &Rect) -> Option<Rect> {
+    let (left, top) = (
+        left.max(i64::from(rect.left())),
+        top.max(i64::from(rect.top())),
+    );
+    let (right, bottom) = (
+        right.min(i64::from(rect.right()) - 1),
+        bottom.min(i64::from(rect.bottom()) - 1),
+    );
+    (left <= right && top <= bottom).then(|| {
+        Rect::try_new(
+            Pos::new(left as i32, top as i32),
+            Pos::new(right as i32 + 1, bottom as i32 + 1),
+        )
+        .unwrap_or_else(|_| unreachable!(/*clipped to a valid rect*/))
+    })
+}
+
+/// Rows `top..` of a canvas buffer, written to in canvas coordinates. Drawing into a band of the
+/// rows touches only those, so disjoint bands can be drawn at the same time.
+struct Band<'a> {
+    pixels: ArrayViewMut2<'a, u32>,
+    top: usize,
+}
+
+impl<'a> Band<'a> {
+    #[inline]
+    fn new(pixels: ArrayViewMut2<'a, u32>, top: usize) -> Self {
+        Self { pixels, top }
+    }
+
+    #[inline]
+    fn set(&mut self, pos: Pos, color: u32) {
+        self.pixels[(pos.y as usize - self.top, pos.x as usize)] = color;
+    }
+
+    #[inline]
+    fn fill(&mut self, rows: Range<usize>, columns: Range<usize>, color: u32) {
+        self.pixels
+            .slice_mut(s![rows.start - self.top..rows.end - self.top, columns])
+            .fill(color);
+    }
+}
+
+#[derive(Error, Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error {
+    #[error("Invalid buffer layout")]
+    InvalidBufferLayout,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colors::{GREEN, RED};
+    use ndarray::array;
+    use proptest::prelude::*;
+    use std::f32::consts::FRAC_PI_2;
+
+    fn image() -> Canvas {
+        let mut image = Canvas::new(0, Size::new(2, 2));
+        image.buffer = array![[0x000001, 0x000002], [0x000003, 0x000004]];
+        image
+    }
+
+    #[test]
+    fn draw_text() {
+        let mut canvas = Canvas::new(0, Size::new(8, 9));
+        canvas.draw_text(Pos::new(1, 1), "T\n.", Font::builtin(), RED, 1);
+        let rows: Vec<String> = canvas
+            .buffer
+            .rows()
+            .into_iter()
+            .map(|row| {
+                row.iter()
+                    .map(|&pixel| if pixel == RED { '#' } else { '.' })
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                "........", ".#####..", "...#....", "...#....", "...#....", "...#....", "...#....",
+                "...#....", "........"
+            ]
+        );
+    }
+
+    #[test]
+    fn draw_text_scaled_in_rect() {
+        let mut canvas = Canvas::new(0, Size::new(6, 4));
+        canvas.draw_text_in_rect(
+            Pos::new(-2, 0),
+            "|",
+            Font::builtin(),
+            RED,
+            2,
+            &Rect::try_new(Pos::new(0, 0), Pos::new(3, 3)).unwrap(),
+        );
+        assert_eq!(
+            canvas.buffer,
+            array![
+                [0, 0, RED, 0, 0, 0],
+                [0, 0, RED, 0, 0, 0],
+                [0, 0, RED, 0, 0, 0],
+                [0, 0, 0, 0, 0, 0]
+            ]
+        );
+        canvas.draw_text(
+            Pos::new(i32::MAX - 1, 0),
+            "xxxxxxxx",
+            Font::builtin(),
+            RED,
+            1000,
+        );
+        canvas.draw_text(Pos::new(0, 0), "x", Font::builtin(), GREEN, 0);
+        assert_eq!(
+            canvas.buffer.iter().filter(|&&pixel| pixel == RED).count(),
+            3
+        );
+    }
+
+    /// The plain Bresenham walk over the whole segment, testing every pixel against `rect`.
+    fn reference_line(buffer: &mut Array2<u32>, from: Pos, to: Pos, color: u32, rect: &Rect) {
         let dx = from.x.abs_diff(to.x) as i32;
         let sx: i32 = if from.x < to.x { 1 } else { -1 };
         let dy = -(from.y.abs_diff(to.y) as i32);
         let sy: i32 = if from.y < to.y { 1 } else { -1 };
         let mut error = dx + dy;
         let mut pos = from;
-
         loop {
-            Self::draw_dot_in_rect_inner(buffer, pos, color, rect);
+            if rect.contains(pos) {
+                buffer[(pos.y as usize, pos.x as usize)] = color;
+            }
             if pos == to {
                 break;
             }
@@ -201,10 +868,315 @@ impl Canvas {
             }
         }
     }
-}
 
-#[derive(Error, Debug, Copy, Clone, Eq, PartialEq)]
-pub enum Error {
-    #[error("Invalid buffer layout")]
-    InvalidBufferLayout,
+    fn pos() -> impl Strategy<Value = Pos> {
+        prop_oneof![
+            (-20..60, -20..50).prop_map(|(x, y)| Pos::new(x, y)),
+            (-5000..5000, -5000..5000).prop_map(|(x, y)| Pos::new(x, y)),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn clipped_line_matches_bresenham(
+            from in pos(),
+            to in pos(),
+            corner in (-10..50, -10..40),
+            size in (0..60u32, 0..50u32),
+        ) {
+            let rect = Rect::try_new_size(Pos::new(corner.0, corner.1), Size::new(size.0, size.1))
+                .unwrap();
+            let mut canvas = Canvas::new(0, Size::new(40, 30));
+            canvas.draw_line_in_rect(from, to, RED, &rect);
+            let mut expected = Array2::zeros((30, 40));
+            if let Some(rect) = &canvas.global_rect & &rect {
+                reference_line(&mut expected, from, to, RED, &rect);
+            }
+            prop_assert_eq!(canvas.buffer, expected);
+        }
+    }
+
+    #[test]
+    fn clipped_short_lines_match_bresenham() {
+        let rect = Rect::try_new(Pos::new(1, 1), Pos::new(5, 4)).unwrap();
+        let points: Vec<_> = (-3..9)
+            .flat_map(|x| (-3..8).map(move |y| Pos::new(x, y)))
+            .collect();
+        for &from in &points {
+            for &to in &points {
+                let mut canvas = Canvas::new(0, Size::new(6, 5));
+                canvas.draw_line_in_rect(from, to, RED, &rect);
+                let mut expected = Array2::zeros((5, 6));
+                reference_line(&mut expected, from, to, RED, &rect);
+                assert_eq!(canvas.buffer, expected, "{from:?} {to:?}");
+            }
+        }
+    }
+
+    /// The per pixel distance test over the bounding box of the circle.
+    fn reference_circle(
+        buffer: &mut Array2<u32>,
+        center: Pos,
+        radius: u32,
+        outline_color: u32,
+        fill_color: u32,
+        rect: &Rect,
+    ) {
+        let radius_i = radius as i32;
+        let r2 = radius * radius;
+        let clamp_x = |x: i32| x.clamp(rect.left(), rect.right() - 1);
+        let x_range = (clamp_x(center.x - radius_i), clamp_x(center.x + radius_i));
+        let clamp_y = |y: i32| y.clamp(rect.top(), rect.bottom() - 1);
+        let y_range = (clamp_y(center.y - radius_i), clamp_y(center.y + radius_i));
+        for x in x_range.0..=x_range.1 {
+            let offset_x = x - center.x;
+            let offset_x_2 = (offset_x * offset_x) as u32;
+            for y in y_range.0..=y_range.1 {
+                let offset_y = y - center.y;
+                let dist = offset_x_2 + (offset_y * offset_y) as u32;
+                if dist < r2 + radius {
+                    buffer[(y as usize, x as usize)] = if dist <= r2 - radius {
+                        fill_color
+                    } else {
+                        outline_color
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn span_circles_match_distance_test() {
+        let rects = [
+            Rect::try_new(Pos::new(0, 0), Pos::new(48, 40)).unwrap(),
+            Rect::try_new(Pos::new(7, 5), Pos::new(30, 21)).unwrap(),
+            Rect::try_new(Pos::new(20, 20), Pos::new(21, 21)).unwrap(),
+        ];
+        for rect in &rects {
+            for radius in 1..30 {
+                for center in [(0, 0), (20, 17), (-9, 12), (47, 39), (60, -3), (24, 45)] {
+                    let center = Pos::new(center.0, center.1);
+                    let mut canvas = Canvas::new(0, Size::new(48, 40));
+                    canvas.draw_circle_in_rect(center, radius, RED, GREEN, rect);
+                    let mut expected = Array2::zeros((40, 48));
+                    reference_circle(&mut expected, center, radius, RED, GREEN, rect);
+                    assert_eq!(canvas.buffer, expected, "{center:?} {radius} {rect:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn large_circle() {
+        let mut canvas = Canvas::new(0, Size::new(3, 2));
+        canvas.draw_circle(Pos::new(1, -1_000_000), 1_000_001, RED, GREEN);
+        assert_eq!(
+            canvas.buffer,
+            array![[GREEN, GREEN, GREEN], [RED, RED, RED]]
+        );
+    }
+
+    #[test]
+    fn far_off_screen_line() {
+        let mut canvas = Canvas::new(0, Size::new(4, 3));
+        canvas.draw_line(Pos::new(i32::MIN, 1), Pos::new(i32::MAX, 1), RED);
+        canvas.draw_line(
+            Pos::new(i32::MAX, i32::MAX),
+            Pos::new(i32::MIN, i32::MIN),
+            GREEN,
+        );
+        assert_eq!(
+            canvas.buffer,
+            array![[GREEN, 0, 0, 0], [RED, GREEN, RED, RED], [0, 0, GREEN, 0]]
+        );
+    }
+
+    /// Changed pixels of `canvas` since `before` that no damaged rect covers.
+    pub(super) fn undamaged_changes(before: &Array2<u32>, canvas: &Canvas) -> Vec<Pos> {
+        before
+            .indexed_iter()
+            .filter(|&((y, x), &color)| canvas.buffer[(y, x)] != color)
+            .map(|((y, x), _)| Pos::new(x as i32, y as i32))
+            .filter(|&pos| !canvas.damage().iter().any(|rect| rect.contains(pos)))
+            .collect()
+    }
+
+    proptest! {
+        #[test]
+        fn damage_covers_changes(
+            shapes in prop::collection::vec((pos(), pos(), 0..20u32, 0..4u8), 0..20),
+            clip in (-10..50, -10..40, 0..60u32, 0..50u32),
+        ) {
+            let rect =
+                Rect::try_new_size(Pos::new(clip.0, clip.1), Size::new(clip.2, clip.3)).unwrap();
+            let mut canvas = Canvas::new(0, Size::new(40, 30));
+            canvas.clear_damage();
+            for (from, to, radius, kind) in shapes {
+                match kind {
+                    0 => canvas.draw_circle_in_rect(from, radius, RED, GREEN, &rect),
+                    1 => canvas.draw_dot_in_rect(from, RED, &rect),
+                    2 => canvas.draw_line_in_rect(from, to, GREEN, &rect),
+                    _ => canvas.draw_text_in_rect(from, "Ab\nc", Font::builtin(), RED, radius % 3, &rect),
+                }
+            }
+            prop_assert!(canvas.damage().len() <= DAMAGE_RECTS_MAX);
+            prop_assert!(canvas.damage().iter().all(|damaged| (damaged & &rect) == Some(*damaged)));
+            prop_assert_eq!(undamaged_changes(&Array2::zeros((30, 40)), &canvas), vec![]);
+        }
+    }
+
+    #[test]
+    fn draw_in_region() {
+        let size = Size::new(24, 16);
+        let region = &Region::from(Rect::try_new(Pos::new(-5, 2), Pos::new(20, 30)).unwrap())
+            - &Region::from(Rect::try_new(Pos::new(6, 4), Pos::new(14, 12)).unwrap());
+        let transform = Affine2::from_scale(Vec2::splat(8.0));
+
+        let mut canvas = Canvas::new(0, size);
+        canvas.draw_circle_in_region(Pos::new(10, 8), 7, RED, GREEN, &region);
+        canvas.draw_line_in_region(Pos::new(0, 0), Pos::new(23, 15), RED, &region);
+        canvas.draw_dot_in_region(Pos::new(19, 3), GREEN, &region);
+        canvas.draw_text_in_region(Pos::new(1, 5), "Hi!", Font::builtin(), RED, 2, &region);
+        canvas.draw_image_in_region(&image(), transform, Filter::Nearest, &region);
+
+        let mut expected = Canvas::new(0, size);
+        expected.draw_circle(Pos::new(10, 8), 7, RED, GREEN);
+        expected.draw_line(Pos::new(0, 0), Pos::new(23, 15), RED);
+        expected.draw_dot(Pos::new(19, 3), GREEN);
+        expected.draw_text(Pos::new(1, 5), "Hi!", Font::builtin(), RED, 2);
+        expected.draw_image(&image(), transform, Filter::Nearest);
+        for ((y, x), color) in expected.buffer.indexed_iter_mut() {
+            if !region.contains(Pos::new(x as i32, y as i32)) {
+                *color = 0;
+            }
+        }
+        assert_eq!(canvas.buffer, expected.buffer);
+    }
+
+    #[test]
+    fn damage() {
+        let mut canvas = Canvas::new(0, Size::new(20, 10));
+        assert_eq!(canvas.damage(), [canvas.rect()]);
+        canvas.clear_damage();
+        assert_eq!(canvas.damage(), []);
+        canvas.draw_dot(Pos::new(1, 1), RED);
+        canvas.draw_dot(Pos::new(2, 1), RED);
+        canvas.draw_circle(Pos::new(15, 6), 2, RED, GREEN);
+        canvas.draw_line(Pos::new(30, 1), Pos::new(-5, 1), GREEN);
+        assert_eq!(
+            canvas.damage(),
+            [
+                Rect::try_new(Pos::new(13, 4), Pos::new(18, 9)).unwrap(),
+                Rect::try_new(Pos::new(0, 1), Pos::new(20, 2)).unwrap(),
+            ]
+        );
+        canvas.draw_image(
+            &image(),
+            Affine2::from_translation(Vec2::new(5.0, 5.0)),
+            Filter::Nearest,
+        );
+        assert_eq!(
+            canvas.damage()[2],
+            Rect::try_new(Pos::new(5, 5), Pos::new(7, 7)).unwrap()
+        );
+        canvas.clear(0);
+        assert_eq!(canvas.damage(), [canvas.rect()]);
+    }
+
+    #[test]
+    fn clear() {
+        let mut canvas = Canvas::new(RED, Size::new(2, 1));
+        canvas.draw_dot(Pos::new(0, 0), GREEN);
+        canvas.clear(GREEN);
+        assert_eq!(canvas.buffer, array![[GREEN, GREEN]]);
+    }
+
+    #[test]
+    fn draw_image_identity() {
+        let mut canvas = Canvas::new(RED, Size::new(3, 3));
+        canvas.draw_image(
+            &image(),
+            Affine2::from_translation(Vec2::new(1.0, 0.0)),
+            Filter::Nearest,
+        );
+        assert_eq!(
+            canvas.buffer,
+            array![
+                [RED, 0x000001, 0x000002],
+                [RED, 0x000003, 0x000004],
+                [RED, RED, RED]
+            ]
+        );
+    }
+
+    #[test]
+    fn draw_image_scaled() {
+        let mut canvas = Canvas::new(RED, Size::new(4, 4));
+        canvas.draw_image(
+            &image(),
+            Affine2::from_scale(Vec2::splat(2.0)),
+            Filter::Nearest,
+        );
+        assert_eq!(
+            canvas.buffer,
+            array![
+                [0x000001, 0x000001, 0x000002, 0x000002],
+                [0x000001, 0x000001, 0x000002, 0x000002],
+                [0x000003, 0x000003, 0x000004, 0x000004],
+                [0x000003, 0x000003, 0x000004, 0x000004]
+            ]
+        );
+    }
+
+    #[test]
+    fn draw_image_rotated() {
+        let mut canvas = Canvas::new(RED, Size::new(2, 2));
+        canvas.draw_image(
+            &image(),
+            Affine2::from_angle_translation(FRAC_PI_2, Vec2::new(2.0, 0.0)),
+            Filter::Nearest,
+        );
+        assert_eq!(
+            canvas.buffer,
+            array![[0x000003, 0x000001], [0x000004, 0x000002]]
+        );
+    }
+
+    #[test]
+    fn draw_image_bilinear() {
+        let mut image = Canvas::new(0, Size::new(2, 1));
+        image.buffer = array![[0x000000, 0x0000FE]];
+        let mut canvas = Canvas::new(RED, Size::new(4, 1));
+        canvas.draw_image(
+            &image,
+            Affine2::from_scale(Vec2::new(2.0, 1.0)),
+            Filter::Bilinear,
+        );
+        assert_eq!(
+            canvas.buffer,
+            array![[0x000000, 0x000040, 0x0000BF, 0x0000FE]]
+        );
+    }
+
+    #[test]
+    fn draw_image_in_rect() {
+        let mut canvas = Canvas::new(RED, Size::new(3, 3));
+        canvas.draw_image_in_rect(
+            &Canvas::new(GREEN, Size::new(3, 3)),
+            Affine2::IDENTITY,
+            Filter::Nearest,
+            &Rect::try_new(Pos::new(1, 1), Pos::new(5, 2)).unwrap(),
+        );
+        assert_eq!(
+            canvas.buffer,
+            array![[RED, RED, RED], [RED, GREEN, GREEN], [RED, RED, RED]]
+        );
+    }
+
+    #[test]
+    fn draw_degenerate_image() {
+        let mut canvas = Canvas::new(RED, Size::new(2, 2));
+        canvas.draw_image(&image(), Affine2::from_scale(Vec2::ZERO), Filter::Bilinear);
+        assert_eq!(canvas.buffer, Array::from_elem((2, 2), RED));
+    }
 }
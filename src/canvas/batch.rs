@@ -0,0 +1,283 @@
+use super::{clipped_bounds, Band};
+use crate::{Canvas, Pos, Rect};
+
+/// Largest radius whose spans are kept as a stamp.
+const STAMP_RADIUS_MAX: u32 = 64;
+
+/// Half widths of the outline and of the fill of one circle row.
+type Span = (u64, Option<u64>);
+
+#[derive(Eq, PartialEq, Debug, Copy, Clone)]
+pub struct Circle {
+    pub center: Pos,
+    pub radius: u32,
+    pub outline_color: u32,
+    pub fill_color: u32,
+}
+
+impl Circle {
+    #[inline]
+    pub fn new(center: Pos, radius: u32, outline_color: u32, fill_color: u32) -> Self {
+        Self {
+            center,
+            radius,
+            outline_color,
+            fill_color,
+        }
+    }
+}
+
+impl From<(Pos, u32, u32, u32)> for Circle {
+    #[inline]
+    fn from((center, radius, outline_color, fill_color): (Pos, u32, u32, u32)) -> Self {
+        Self::new(center, radius, outline_color, fill_color)
+    }
+}
+
+#[derive(Eq, PartialEq, Debug, Copy, Clone)]
+pub struct BatchOptions {
+    /// Width and height of the tiles circles are binned into.
+    pub tile_size: u32,
+    /// Keep the spans of every radius up to 64 pixels instead of computing them per circle.
+    pub stamps: bool,
+}
+
+impl Default for BatchOptions {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            tile_size: 64,
+            stamps: true,
+        }
+    }
+}
+
+/// Draws many circles at once, with the same pixels as drawing them one by one in order.
+///
+/// Circles outside the clip rect are culled and the rest are binned by the tiles their bounding
+/// boxes touch. Tiles are then drawn one after another, each with its circles in their original
+/// order, so overlapping circles still cover each other as in single calls. The bins and stamps
+/// are kept between calls.
+pub struct CircleBatch {
+    options: BatchOptions,
+    bins: Vec<(usize, usize)>,
+    stamps: Vec<Option<Box<[Span]>>>,
+}
+
+impl CircleBatch {
+    #[inline]
+    pub fn new(options: BatchOptions) -> Self {
+        Self {
+            options,
+            bins: vec![],
+            stamps: vec![],
+        }
+    }
+
+    #[inline]
+    pub fn draw(&mut self, canvas: &mut Canvas, circles: &[Circle]) {
+        let rect = canvas.rect();
+        self.draw_in_rect(canvas, circles, &rect);
+    }
+
+    pub fn draw_in_rect(&mut self, canvas: &mut Canvas, circles: &[Circle], rect: &Rect) {
+        let Some(clip) = &canvas.global_rect & rect else {
+            return;
+        };
+        if clip.is_zero_size() {
+            return;
+        }
+        let tile_size = i64::from(self.options.tile_size.max(1));
+        let tiles_x = (i64::from(clip.width()) + tile_size - 1) / tile_size;
+        let (origin_x, origin_y) = (i64::from(clip.left()), i64::from(clip.top()));
+
+        self.bins.clear();
+        for (index, circle) in circles.iter().enumerate() {
+            let bounds = clipped_bounds(Canvas::circle_bounds(circle.center, circle.radius), &clip);
+            let Some(bounds) = bounds else {
+                continue;
+            };
+            canvas.add_damage(Some(bounds));
+            let tiles = |low: i32, high: i32, origin: i64| {
+                (i64::from(low) - origin) / tile_size..=(i64::from(high) - 1 - origin) / tile_size
+            };
+            for tile_y in tiles(bounds.top(), bounds.bottom(), origin_y) {
+                for tile_x in tiles(bounds.left(), bounds.right(), origin_x) {
+                    self.bins
+                        .push(((tile_y * tiles_x + tile_x) as usize, index));
+                }
+            }
+        }
+        self.bins.sort_unstable();
+
+        let mut buffer = Band::new(canvas.buffer.view_mut(), 0);
+        for bin in self.bins.chunk_by(|a, b| a.0 == b.0) {
+            let tile = bin[0].0 as i64;
+            let left = origin_x + tile % tiles_x * tile_size;
+            let top = origin_y + tile / tiles_x * tile_size;
+            let tile_rect = Rect::try_new(
+                Pos::new(left as i32, top as i32),
+                Pos::new(
+                    (left + tile_size).min(i64::from(clip.right())) as i32,
+                    (top + tile_size).min(i64::from(clip.bottom())) as i32,
+                ),
+            )
+            .unwrap_or_else(|_| unreachable!(/*tiles lie within the clip rect*/));
+            for &(_, index) in bin {
+                draw_circle(
+                    &mut buffer,
+                    &mut self.stamps,
+                    self.options.stamps,
+                    &circles[index],
+                    &tile_rect,
+                );
+            }
+        }
+    }
+}
+
+impl Default for CircleBatch {
+    #[inline]
+    fn default() -> Self {
+        Self::new(BatchOptions::default())
+    }
+}
+
+fn draw_circle(
+    buffer: &mut Band,
+    stamps: &mut Vec<Option<Box<[Span]>>>,
+    use_stamps: bool,
+    circle: &Circle,
+    rect: &Rect,
+) {
+    let &Circle {
+        center,
+        radius,
+        outline_color,
+        fill_color,
+    } = circle;
+    if !use_stamps || radius == 0 || radius > STAMP_RADIUS_MAX {
+        Canvas::draw_dot_or_circle_in_rect_inner(
+            buffer,
+            center,
+            radius,
+            outline_color,
+            fill_color,
+            rect,
+        );
+        return;
+    }
+    let Some(rows) = Canvas::circle_rows(center, radius, rect) else {
+        return;
+    };
+    if stamps.len() <= radius as usize {
+        stamps.resize(radius as usize + 1, None);
+    }
+    let stamp = stamps[radius as usize].get_or_insert_with(|| stamp(radius));
+    for dy in rows {
+        Canvas::draw_circle_rows_inner(
+            buffer,
+            center,
+            dy,
+            stamp[dy as usize],
+            (outline_color, fill_color),
+            rect,
+        );
+    }
+}
+
+/// Spans of the rows `0..=radius` away from the center, see
+/// [`Canvas::draw_circle_in_rect_inner`].
+fn stamp(radius: u32) -> Box<[Span]> {
+    let radius = u64::from(radius);
+    let outline_limit = radius * radius + radius - 1;
+    let fill_limit = radius * radius - radius;
+    (0..=radius)
+        .map(|dy| {
+            let outline = (outline_limit - dy * dy).isqrt();
+            let fill = fill_limit.checked_sub(dy * dy).map(u64::isqrt);
+            (outline, fill)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Size;
+    use proptest::prelude::*;
+
+    fn circles() -> impl Strategy<Value = Vec<Circle>> {
+        prop::collection::vec(
+            (-20..80, -20..70, 0..40u32, any::<u32>(), any::<u32>()).prop_map(
+                |(x, y, radius, outline_color, fill_color)| {
+                    Circle::new(Pos::new(x, y), radius, outline_color, fill_color)
+                },
+            ),
+            0..40,
+        )
+    }
+
+    proptest! {
+        #[test]
+        fn batch_matches_single_circles(
+            circles in circles(),
+            tile_size in 1..40u32,
+            stamps: bool,
+            clip in (-5..30, -5..30, 0..60, 0..60),
+        ) {
+            let size = Size::new(60, 50);
+            let rect = Rect::try_new(
+                Pos::new(clip.0, clip.1),
+                Pos::new(clip.0 + clip.2, clip.1 + clip.3),
+            )
+            .unwrap();
+            let mut expected = Canvas::new(0, size);
+            expected.clear_damage();
+            for circle in &circles {
+                expected.draw_circle_in_rect(
+                    circle.center,
+                    circle.radius,
+                    circle.outline_color,
+                    circle.fill_color,
+                    &rect,
+                );
+            }
+            let mut canvas = Canvas::new(0, size);
+            canvas.clear_damage();
+            CircleBatch::new(BatchOptions { tile_size, stamps }).draw_in_rect(
+                &mut canvas,
+                &circles,
+                &rect,
+            );
+            prop_assert_eq!(canvas.pixels(), expected.pixels());
+            prop_assert_eq!(canvas.damage(), expected.damage());
+        }
+    }
+
+    #[test]
+    fn reuse_batch() {
+        let mut batch = CircleBatch::default();
+        let mut canvas = Canvas::new(0, Size::new(200, 100));
+        let circles = [
+            Circle::new(Pos::new(50, 50), 30, 1, 2),
+            (Pos::new(70, 40), 100, 3, 4).into(),
+            Circle::new(Pos::new(-500, 50), 30, 5, 6),
+            Circle::new(Pos::new(199, 0), 0, 7, 8),
+        ];
+        let mut expected = Canvas::new(0, Size::new(200, 100));
+        for circle in &circles {
+            expected.draw_circle(
+                circle.center,
+                circle.radius,
+                circle.outline_color,
+                circle.fill_color,
+            );
+        }
+        for _ in 0..2 {
+            canvas.clear(0);
+            batch.draw(&mut canvas, &circles);
+            assert_eq!(canvas.pixels(), expected.pixels());
+        }
+    }
+}
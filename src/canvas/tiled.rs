@@ -0,0 +1,312 @@
+use super::{clipped_bounds, Band, Circle};
+use crate::{Canvas, Draw, Pos, Rect, Size};
+use ndarray::Axis;
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+use std::thread;
+
+#[derive(Eq, PartialEq, Debug, Copy, Clone)]
+pub struct TileOptions {
+    /// Height of the row bands the canvas is split into.
+    pub band_height: u32,
+    /// Threads drawing the bands, the calling thread draws alone if this is at most 1.
+    pub threads: usize,
+}
+
+impl Default for TileOptions {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            band_height: 32,
+            threads: thread::available_parallelism().map_or(1, NonZeroUsize::get),
+        }
+    }
+}
+
+#[derive(Eq, PartialEq, Debug, Copy, Clone)]
+enum Primitive {
+    Circle(Circle),
+    Dot(Pos, u32),
+    Line(Pos, Pos, u32),
+}
+
+impl Primitive {
+    /// Bounding rect within `clip`, if it reaches into it.
+    #[inline]
+    fn bounds(&self, clip: &Rect) -> Option<Rect> {
+        let bounds = match *self {
+            Self::Circle(circle) => Canvas::circle_bounds(circle.center, circle.radius),
+            Self::Dot(pos, _) => Canvas::circle_bounds(pos, 0),
+            Self::Line(from, to, _) => Canvas::line_bounds(from, to),
+        };
+        clipped_bounds(bounds, clip)
+    }
+
+    #[inline]
+    fn draw(&self, buffer: &mut Band, rect: &Rect) {
+        match *self {
+            Self::Circle(circle) => Canvas::draw_dot_or_circle_in_rect_inner(
+                buffer,
+                circle.center,
+                circle.radius,
+                circle.outline_color,
+                circle.fill_color,
+                rect,
+            ),
+            Self::Dot(pos, color) => Canvas::draw_dot_in_rect_inner(buffer, pos, color, rect),
+            Self::Line(from, to, color) => {
+                Canvas::draw_line_in_rect_inner(buffer, from, to, color, rect)
+            }
+        }
+    }
+}
+
+/// Records drawing calls and rasterizes them on several threads.
+///
+/// The canvas is split into row bands and every recorded primitive is binned into the bands its
+/// bounding box touches. The bands are then drawn in parallel, each with its primitives in the
+/// order they were recorded, which gives the same pixels as drawing into the canvas directly.
+pub struct TiledRenderer {
+    options: TileOptions,
+    size: Size,
+    /// Primitives with their clip rect, `None` for the whole canvas.
+    primitives: Vec<(Primitive, Option<Rect>)>,
+}
+
+impl TiledRenderer {
+    /// Creates a renderer reporting `size` through [`Draw::size`], which should be the size of
+    /// the canvas it renders into.
+    #[inline]
+    pub fn new(size: Size, options: TileOptions) -> Self {
+        Self {
+            options,
+            size,
+            primitives: vec![],
+        }
+    }
+
+    /// Drops the recorded primitives without drawing them.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.primitives.clear();
+    }
+
+    /// Draws the recorded primitives into `canvas` and clears them.
+    pub fn render(&mut self, canvas: &mut Canvas) {
+        let global_rect = canvas.global_rect;
+        let band_height = self.options.band_height.max(1) as usize;
+        let band_count = (global_rect.height() as usize).div_ceil(band_height);
+
+        let mut clips = Vec::with_capacity(self.primitives.len());
+        let mut bins = vec![vec![]; band_count];
+        for (index, (primitive, rect)) in self.primitives.iter().enumerate() {
+            let clip = match rect {
+                Some(rect) => &global_rect & rect,
+                None => Some(global_rect),
+            };
+            clips.push(clip.unwrap_or_default());
+            let Some(bounds) = clip.and_then(|clip| primitive.bounds(&clip)) else {
+                continue;
+            };
+            canvas.add_damage(Some(bounds));
+            let (top, bottom) = (bounds.top() as usize, bounds.bottom() as usize - 1);
+            for bin in &mut bins[top / band_height..=bottom / band_height] {
+                bin.push(index);
+            }
+        }
+
+        let primitives = &self.primitives;
+        let clips = &clips;
+        let draw_band = |(band, (pixels, bin)): (usize, (_, Vec<usize>))| {
+            let top = band * band_height;
+            let mut buffer = Band::new(pixels, top);
+            let band_rect = Rect::try_new(
+                Pos::new(global_rect.left(), top as i32),
+                Pos::new(
+                    global_rect.right(),
+                    (top + band_height).min(global_rect.bottom() as usize) as i32,
+                ),
+            )
+            .unwrap_or_else(|_| unreachable!(/*bands lie within the canvas*/));
+            for index in bin {
+                if let Some(rect) = &clips[index] & &band_rect {
+                    if !rect.is_zero_size() {
+                        primitives[index].0.draw(&mut buffer, &rect);
+                    }
+                }
+            }
+        };
+        let bands = canvas
+            .buffer
+            .axis_chunks_iter_mut(Axis(0), band_height)
+            .zip(bins)
+            .enumerate();
+        let threads = self.options.threads.min(band_count);
+        if threads <= 1 {
+            bands.for_each(draw_band);
+        } else {
+            let bands = Mutex::new(bands);
+            thread::scope(|scope| {
+                for _ in 0..threads {
+                    scope.spawn(|| loop {
+                        let Some(band) = bands.lock().unwrap_or_else(|err| err.into_inner()).next()
+                        else {
+                            break;
+                        };
+                        draw_band(band);
+                    });
+                }
+            });
+        }
+        self.primitives.clear();
+    }
+}
+
+impl Draw for TiledRenderer {
+    #[inline]
+    fn size(&self) -> Size {
+        self.size
+    }
+
+    #[inline]
+    fn draw_circle_in_rect(
+        &mut self,
+        center: Pos,
+        radius: u32,
+        outline_color: u32,
+        fill_color: u32,
+        rect: &Rect,
+    ) {
+        let circle = Circle::new(center, radius, outline_color, fill_color);
+        self.primitives
+            .push((Primitive::Circle(circle), Some(*rect)));
+    }
+
+    #[inline]
+    fn draw_dot_in_rect(&mut self, pos: Pos, color: u32, rect: &Rect) {
+        self.primitives
+            .push((Primitive::Dot(pos, color), Some(*rect)));
+    }
+
+    #[inline]
+    fn draw_line_in_rect(&mut self, from: Pos, to: Pos, color: u32, rect: &Rect) {
+        self.primitives
+            .push((Primitive::Line(from, to, color), Some(*rect)));
+    }
+
+    #[inline]
+    fn draw_circle(&mut self, center: Pos, radius: u32, outline_color: u32, fill_color: u32) {
+        let circle = Circle::new(center, radius, outline_color, fill_color);
+        self.primitives.push((Primitive::Circle(circle), None));
+    }
+
+    #[inline]
+    fn draw_dot(&mut self, pos: Pos, color: u32) {
+        self.primitives.push((Primitive::Dot(pos, color), None));
+    }
+
+    #[inline]
+    fn draw_line(&mut self, from: Pos, to: Pos, color: u32) {
+        self.primitives
+            .push((Primitive::Line(from, to, color), None));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene;
+    use proptest::prelude::*;
+
+    #[derive(Debug, Clone)]
+    enum Call {
+        Circle(Circle, Option<Rect>),
+        Dot(Pos, u32, Option<Rect>),
+        Line(Pos, Pos, u32, Option<Rect>),
+    }
+
+    impl Call {
+        fn apply(&self, draw: &mut impl Draw) {
+            match *self {
+                Self::Circle(circle, None) => draw.draw_circle(
+                    circle.center,
+                    circle.radius,
+                    circle.outline_color,
+                    circle.fill_color,
+                ),
+                Self::Circle(circle, Some(rect)) => draw.draw_circle_in_rect(
+                    circle.center,
+                    circle.radius,
+                    circle.outline_color,
+                    circle.fill_color,
+                    &rect,
+                ),
+                Self::Dot(pos, color, None) => draw.draw_dot(pos, color),
+                Self::Dot(pos, color, Some(rect)) => draw.draw_dot_in_rect(pos, color, &rect),
+                Self::Line(from, to, color, None) => draw.draw_line(from, to, color),
+                Self::Line(from, to, color, Some(rect)) => {
+                    draw.draw_line_in_rect(from, to, color, &rect)
+                }
+            }
+        }
+    }
+
+    fn calls() -> impl Strategy<Value = Vec<Call>> {
+        let pos = || (-30..100, -30..90).prop_map(|(x, y)| Pos::new(x, y));
+        let rect = prop::option::of((-10..60, -10..60, 0..60, 0..60).prop_map(
+            |(x, y, width, height)| {
+                Rect::try_new(Pos::new(x, y), Pos::new(x + width, y + height)).unwrap()
+            },
+        ));
+        let call = prop_oneof![
+            (pos(), 0..50u32, any::<u32>(), any::<u32>(), rect.clone()).prop_map(
+                |(center, radius, outline_color, fill_color, rect)| {
+                    Call::Circle(Circle::new(center, radius, outline_color, fill_color), rect)
+                }
+            ),
+            (pos(), any::<u32>(), rect.clone())
+                .prop_map(|(pos, color, rect)| Call::Dot(pos, color, rect)),
+            (pos(), pos(), any::<u32>(), rect)
+                .prop_map(|(from, to, color, rect)| Call::Line(from, to, color, rect)),
+        ];
+        prop::collection::vec(call, 0..30)
+    }
+
+    proptest! {
+        #[test]
+        fn tiled_matches_serial(
+            calls in calls(),
+            band_height in 1..40u32,
+            threads in 1..5usize,
+        ) {
+            let size = Size::new(70, 60);
+            let mut expected = Canvas::new(0, size);
+            expected.clear_damage();
+            let mut renderer = TiledRenderer::new(size, TileOptions { band_height, threads });
+            for call in &calls {
+                call.apply(&mut expected);
+                call.apply(&mut renderer);
+            }
+            let mut canvas = Canvas::new(0, size);
+            canvas.clear_damage();
+            renderer.render(&mut canvas);
+            prop_assert_eq!(canvas.pixels(), expected.pixels());
+            prop_assert_eq!(canvas.damage(), expected.damage());
+        }
+    }
+
+    #[test]
+    fn render_scene() {
+        let size = Size::new(320, 240);
+        let mut expected = Canvas::new(0, size);
+        scene::draw(&mut expected);
+        let mut renderer = TiledRenderer::new(size, TileOptions::default());
+        let mut canvas = Canvas::new(0, size);
+        for _ in 0..2 {
+            canvas.clear(0);
+            scene::draw(&mut renderer);
+            renderer.render(&mut canvas);
+            assert_eq!(canvas.pixels(), expected.pixels());
+        }
+    }
+}
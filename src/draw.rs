@@ -0,0 +1,26 @@
+use crate::{Pos, Rect, Size};
+
+/// Drawing calls shared by every backend, so the same scene can be rasterized into a
+/// [`Canvas`](crate::Canvas) or recorded, e.g. as vector graphics.
+pub trait Draw {
+    fn size(&self) -> Size;
+
+    fn draw_circle_in_rect(
+        &mut self,
+        center: Pos,
+        radius: u32,
+        outline_color: u32,
+        fill_color: u32,
+        rect: &Rect,
+    );
+
+    fn draw_dot_in_rect(&mut self, pos: Pos, color: u32, rect: &Rect);
+
+    fn draw_line_in_rect(&mut self, from: Pos, to: Pos, color: u32, rect: &Rect);
+
+    fn draw_circle(&mut self, center: Pos, radius: u32, outline_color: u32, fill_color: u32);
+
+    fn draw_dot(&mut self, pos: Pos, color: u32);
+
+    fn draw_line(&mut self, from: Pos, to: Pos, color: u32);
+}
@@ -0,0 +1,38 @@
+use crate::Size;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Canvas(#[from] crate::canvas::Error),
+    #[error(transparent)]
+    Rect(#[from] crate::rect::Error),
+    #[error("Frame size {actual} differs from stream frame size {expected}")]
+    FrameSizeMismatch { expected: Size, actual: Size },
+    #[error("Unknown image format")]
+    UnknownImageFormat,
+    #[error("Invalid Netpbm image: {0}")]
+    InvalidNetpbm(&'static str),
+    #[error("Invalid QOI image: {0}")]
+    InvalidQoi(&'static str),
+    #[cfg(feature = "bmp")]
+    #[error("Invalid BMP image: {0}")]
+    InvalidBmp(&'static str),
+    #[cfg(feature = "window")]
+    #[error("Softbuffer error: {0}")]
+    Softbuffer(String),
+    #[error("Invalid BDF font: {0}")]
+    InvalidBdf(&'static str),
+    #[error("Invalid PSF font: {0}")]
+    InvalidPsf(&'static str),
+    #[error("GIF frames are limited to 65535x65535, got {0}")]
+    GifTooLarge(Size),
+    #[cfg(feature = "png")]
+    #[error("PNG encoding error: {0}")]
+    PngEncoding(#[from] png::EncodingError),
+    #[cfg(feature = "png")]
+    #[error("PNG decoding error: {0}")]
+    PngDecoding(#[from] png::DecodingError),
+}
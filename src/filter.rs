@@ -0,0 +1,121 @@
+use glam::Vec2;
+use ndarray::Array2;
+
+#[derive(Eq, PartialEq, Debug, Copy, Clone, Default)]
+pub enum Filter {
+    #[default]
+    Nearest,
+    Bilinear,
+}
+
+impl Filter {
+    /// Samples `src` at `pos` given in source pixel space, where pixel `(x, y)` covers
+    /// `[x, x + 1) x [y, y + 1)`. Returns `None` when `pos` lies outside the source.
+    #[inline]
+    pub fn sample(self, src: &Array2<u32>, pos: Vec2) -> Option<u32> {
+        let (height, width) = src.dim();
+        if !(0.0..width as f32).contains(&pos.x) || !(0.0..height as f32).contains(&pos.y) {
+            return None;
+        }
+        Some(match self {
+            Filter::Nearest => src[(pos.y as usize, pos.x as usize)],
+            Filter::Bilinear => Self::sample_bilinear(src, pos - 0.5),
+        })
+    }
+
+    fn sample_bilinear(src: &Array2<u32>, pos: Vec2) -> u32 {
+        let (height, width) = src.dim();
+        let floor = pos.floor();
+        let fract = pos - floor;
+        let clamp = |v: f32, len: usize| (v.max(0.0) as usize).min(len - 1);
+        let (x0, x1) = (clamp(floor.x, width), clamp(floor.x + 1.0, width));
+        let (y0, y1) = (clamp(floor.y, height), clamp(floor.y + 1.0, height));
+
+        let top = lerp_color(src[(y0, x0)], src[(y0, x1)], fract.x);
+        let bottom = lerp_color(src[(y1, x0)], src[(y1, x1)], fract.x);
+        let color = lerp_color_f32(top, bottom, fract.y);
+        color.iter().fold(0, |acc, &channel| {
+            (acc << 8) | channel.round().clamp(0.0, 255.0) as u32
+        })
+    }
+}
+
+#[inline]
+fn channels(color: u32) -> [f32; 3] {
+    [
+        ((color >> 16) & 0xFF) as f32,
+        ((color >> 8) & 0xFF) as f32,
+        (color & 0xFF) as f32,
+    ]
+}
+
+#[inline]
+fn lerp_color(a: u32, b: u32, t: f32) -> [f32; 3] {
+    lerp_color_f32(channels(a), channels(b), t)
+}
+
+#[inline]
+fn lerp_color_f32(a: [f32; 3], b: [f32; 3], t: f32) -> [f32; 3] {
+    [
+        a[0] + (b[0] - a[0]) * t,
+        a[1] + (b[1] - a[1]) * t,
+        a[2] + (b[2] - a[2]) * t,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+
+    #[test]
+    fn nearest() {
+        let src = array![[0x010203, 0x040506], [0x070809, 0x0A0B0C]];
+        assert_eq!(
+            Filter::Nearest.sample(&src, Vec2::new(1.9, 0.1)),
+            Some(0x040506)
+        );
+        assert_eq!(
+            Filter::Nearest.sample(&src, Vec2::new(0.0, 1.5)),
+            Some(0x070809)
+        );
+    }
+
+    #[test]
+    fn outside() {
+        let src = array![[0x010203]];
+        assert_eq!(Filter::Nearest.sample(&src, Vec2::new(-0.1, 0.5)), None);
+        assert_eq!(Filter::Bilinear.sample(&src, Vec2::new(0.5, 1.0)), None);
+    }
+
+    #[test]
+    fn bilinear_center_is_exact() {
+        let src = array![[0x000000, 0xFF0000], [0x00FF00, 0x0000FF]];
+        assert_eq!(
+            Filter::Bilinear.sample(&src, Vec2::new(1.5, 0.5)),
+            Some(0xFF0000)
+        );
+    }
+
+    #[test]
+    fn bilinear_between_pixels() {
+        let src = array![[0x000000, 0xFE0000], [0x00FE00, 0x0000FE]];
+        assert_eq!(
+            Filter::Bilinear.sample(&src, Vec2::new(1.0, 0.5)),
+            Some(0x7F0000)
+        );
+        assert_eq!(
+            Filter::Bilinear.sample(&src, Vec2::new(1.0, 1.0)),
+            Some(0x404040)
+        );
+    }
+
+    #[test]
+    fn bilinear_clamps_to_edge() {
+        let src = array![[0x102030, 0x405060]];
+        assert_eq!(
+            Filter::Bilinear.sample(&src, Vec2::new(0.1, 0.9)),
+            Some(0x102030)
+        );
+    }
+}
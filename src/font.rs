@@ -0,0 +1,342 @@
+mod bdf;
+mod psf;
+
+use crate::Size;
+use ndarray::{s, Array2, ArrayView2};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+const BUILTIN_FIRST: char = ' ';
+const BUILTIN_WIDTH: usize = 5;
+const BUILTIN_LINE_HEIGHT: usize = 8;
+
+/// The built-in 5x7 font for printable ASCII, one byte per row with the leftmost pixel in bit 4.
+#[rustfmt::skip]
+const BUILTIN_GLYPHS: [[u8; 7]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // space
+    [0x04, 0x04, 0x04, 0x04, 0x04, 0x00, 0x04], // !
+    [0x0A, 0x0A, 0x0A, 0x00, 0x00, 0x00, 0x00], // "
+    [0x0A, 0x0A, 0x1F, 0x0A, 0x1F, 0x0A, 0x0A], // #
+    [0x04, 0x0F, 0x14, 0x0E, 0x05, 0x1E, 0x04], // $
+    [0x18, 0x19, 0x02, 0x04, 0x08, 0x13, 0x03], // %
+    [0x0C, 0x12, 0x14, 0x08, 0x15, 0x12, 0x0D], // &
+    [0x04, 0x04, 0x04, 0x00, 0x00, 0x00, 0x00], // '
+    [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02], // (
+    [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08], // )
+    [0x00, 0x04, 0x15, 0x0E, 0x15, 0x04, 0x00], // *
+    [0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00], // +
+    [0x00, 0x00, 0x00, 0x00, 0x0C, 0x04, 0x08], // ,
+    [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00], // -
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C], // .
+    [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00], // /
+    [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E], // 0
+    [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E], // 1
+    [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F], // 2
+    [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E], // 3
+    [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02], // 4
+    [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E], // 5
+    [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E], // 6
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08], // 7
+    [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E], // 8
+    [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C], // 9
+    [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00], // :
+    [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x04, 0x08], // ;
+    [0x02, 0x04, 0x08, 0x10, 0x08, 0x04, 0x02], // <
+    [0x00, 0x00, 0x1F, 0x00, 0x1F, 0x00, 0x00], // =
+    [0x08, 0x04, 0x02, 0x01, 0x02, 0x04, 0x08], // >
+    [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04], // ?
+    [0x0E, 0x11, 0x01, 0x0D, 0x15, 0x15, 0x0E], // @
+    [0x0E, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11], // A
+    [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E], // B
+    [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E], // C
+    [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C], // D
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F], // E
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10], // F
+    [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F], // G
+    [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11], // H
+    [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E], // I
+    [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C], // J
+    [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11], // K
+    [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F], // L
+    [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11], // M
+    [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11], // N
+    [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E], // O
+    [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10], // P
+    [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D], // Q
+    [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11], // R
+    [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E], // S
+    [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04], // T
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E], // U
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04], // V
+    [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A], // W
+    [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11], // X
+    [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04], // Y
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F], // Z
+    [0x0E, 0x08, 0x08, 0x08, 0x08, 0x08, 0x0E], // [
+    [0x00, 0x10, 0x08, 0x04, 0x02, 0x01, 0x00], // \
+    [0x0E, 0x02, 0x02, 0x02, 0x02, 0x02, 0x0E], // ]
+    [0x04, 0x0A, 0x11, 0x00, 0x00, 0x00, 0x00], // ^
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F], // _
+    [0x08, 0x04, 0x02, 0x00, 0x00, 0x00, 0x00], // `
+    [0x00, 0x00, 0x0E, 0x01, 0x0F, 0x11, 0x0F], // a
+    [0x10, 0x10, 0x16, 0x19, 0x11, 0x11, 0x1E], // b
+    [0x00, 0x00, 0x0E, 0x10, 0x10, 0x11, 0x0E], // c
+    [0x01, 0x01, 0x0D, 0x13, 0x11, 0x11, 0x0F], // d
+    [0x00, 0x00, 0x0E, 0x11, 0x1F, 0x10, 0x0E], // e
+    [0x06, 0x09, 0x08, 0x1C, 0x08, 0x08, 0x08], // f
+    [0x00, 0x0F, 0x11, 0x11, 0x0F, 0x01, 0x0E], // g
+    [0x10, 0x10, 0x16, 0x19, 0x11, 0x11, 0x11], // h
+    [0x04, 0x00, 0x0C, 0x04, 0x04, 0x04, 0x0E], // i
+    [0x02, 0x00, 0x06, 0x02, 0x02, 0x12, 0x0C], // j
+    [0x10, 0x10, 0x12, 0x14, 0x18, 0x14, 0x12], // k
+    [0x0C, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E], // l
+    [0x00, 0x00, 0x1A, 0x15, 0x15, 0x11, 0x11], // m
+    [0x00, 0x00, 0x16, 0x19, 0x11, 0x11, 0x11], // n
+    [0x00, 0x00, 0x0E, 0x11, 0x11, 0x11, 0x0E], // o
+    [0x00, 0x00, 0x1E, 0x11, 0x1E, 0x10, 0x10], // p
+    [0x00, 0x00, 0x0D, 0x13, 0x0F, 0x01, 0x01], // q
+    [0x00, 0x00, 0x16, 0x19, 0x10, 0x10, 0x10], // r
+    [0x00, 0x00, 0x0E, 0x10, 0x0E, 0x01, 0x1E], // s
+    [0x08, 0x08, 0x1C, 0x08, 0x08, 0x09, 0x06], // t
+    [0x00, 0x00, 0x11, 0x11, 0x11, 0x13, 0x0D], // u
+    [0x00, 0x00, 0x11, 0x11, 0x11, 0x0A, 0x04], // v
+    [0x00, 0x00, 0x11, 0x11, 0x15, 0x15, 0x0A], // w
+    [0x00, 0x00, 0x11, 0x0A, 0x04, 0x0A, 0x11], // x
+    [0x00, 0x00, 0x11, 0x11, 0x0F, 0x01, 0x0E], // y
+    [0x00, 0x00, 0x1F, 0x02, 0x04, 0x08, 0x1F], // z
+    [0x02, 0x04, 0x04, 0x08, 0x04, 0x04, 0x02], // {
+    [0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04], // |
+    [0x08, 0x04, 0x04, 0x02, 0x04, 0x04, 0x08], // }
+    [0x00, 0x00, 0x08, 0x15, 0x02, 0x00, 0x00], // ~
+];
+
+/// Where a glyph is placed in the atlas of its [`Font`] and how far it moves the pen.
+#[derive(Eq, PartialEq, Debug, Copy, Clone)]
+pub struct Glyph {
+    x: u32,
+    width: u32,
+    left: i32,
+    advance: u32,
+}
+
+impl Glyph {
+    #[inline]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Horizontal offset of the bitmap from the pen position.
+    #[inline]
+    pub fn left(&self) -> i32 {
+        self.left
+    }
+
+    #[inline]
+    pub fn advance(&self) -> u32 {
+        self.advance
+    }
+}
+
+/// Bitmap of a glyph before it is packed into the atlas of a [`Font`].
+pub(crate) struct GlyphBitmap {
+    /// Characters mapped to this glyph.
+    pub chars: Vec<char>,
+    /// Pixels as tall as the line, with the top row at the top of the line.
+    pub pixels: Array2<bool>,
+    pub left: i32,
+    pub advance: u32,
+}
+
+/// Monochrome bitmap font.
+///
+/// All glyph bitmaps sit side by side in one atlas as tall as a line, so a glyph is drawn by
+/// copying its columns of the atlas to the pen position. Characters without a glyph are drawn
+/// with the fallback glyph, if any.
+pub struct Font {
+    atlas: Array2<bool>,
+    glyphs: Vec<Glyph>,
+    chars: HashMap<char, usize>,
+    fallback: Option<usize>,
+}
+
+impl Font {
+    /// Fixed-width 5x7 font covering printable ASCII, with a 6x8 cell per character.
+    pub fn builtin() -> &'static Font {
+        static BUILTIN: OnceLock<Font> = OnceLock::new();
+        BUILTIN.get_or_init(|| {
+            let atlas = Array2::from_shape_fn(
+                (BUILTIN_LINE_HEIGHT, BUILTIN_GLYPHS.len() * BUILTIN_WIDTH),
+                |(y, x)| {
+                    let rows = &BUILTIN_GLYPHS[x / BUILTIN_WIDTH];
+                    let bit = BUILTIN_WIDTH - 1 - x % BUILTIN_WIDTH;
+                    rows.get(y).is_some_and(|row| row >> bit & 1 != 0)
+                },
+            );
+            let glyphs = (0..BUILTIN_GLYPHS.len())
+                .map(|index| Glyph {
+                    x: (index * BUILTIN_WIDTH) as u32,
+                    width: BUILTIN_WIDTH as u32,
+                    left: 0,
+                    advance: BUILTIN_WIDTH as u32 + 1,
+                })
+                .collect();
+            let chars = (BUILTIN_FIRST..)
+                .take(BUILTIN_GLYPHS.len())
+                .enumerate()
+                .map(|(index, c)| (c, index))
+                .collect();
+            Font {
+                atlas,
+                glyphs,
+                chars,
+                fallback: Some(usize::from(b'?' - BUILTIN_FIRST as u8)),
+            }
+        })
+    }
+
+    /// Packs `glyphs` into one atlas. The first glyph listing a character wins, U+FFFD or `?`
+    /// become the fallback.
+    pub(crate) fn from_glyphs(line_height: u32, glyphs: Vec<GlyphBitmap>) -> Self {
+        let width = glyphs.iter().map(|glyph| glyph.pixels.ncols()).sum();
+        let mut atlas = Array2::from_elem((line_height as usize, width), false);
+        let mut chars = HashMap::new();
+        let mut x = 0;
+        let glyphs = glyphs
+            .into_iter()
+            .enumerate()
+            .map(|(index, glyph)| {
+                let width = glyph.pixels.ncols();
+                atlas.slice_mut(s![.., x..x + width]).assign(&glyph.pixels);
+                for c in glyph.chars {
+                    chars.entry(c).or_insert(index);
+                }
+                let packed = Glyph {
+                    x: x as u32,
+                    width: width as u32,
+                    left: glyph.left,
+                    advance: glyph.advance,
+                };
+                x += width;
+                packed
+            })
+            .collect();
+        let fallback = ['\u{FFFD}', '?'].iter().find_map(|c| chars.get(c).copied());
+        Self {
+            atlas,
+            glyphs,
+            chars,
+            fallback,
+        }
+    }
+
+    /// Number of glyphs in the atlas, several characters may share one.
+    #[inline]
+    pub fn glyph_count(&self) -> usize {
+        self.glyphs.len()
+    }
+
+    #[inline]
+    pub fn line_height(&self) -> u32 {
+        self.atlas.nrows() as u32
+    }
+
+    /// Glyph drawn for `c`, the fallback glyph if the font has none for it.
+    #[inline]
+    pub fn glyph(&self, c: char) -> Option<Glyph> {
+        self.chars
+            .get(&c)
+            .copied()
+            .or(self.fallback)
+            .map(|index| self.glyphs[index])
+    }
+
+    /// Bitmap of `glyph`, `line_height` rows by `glyph.width()` columns.
+    #[inline]
+    pub fn glyph_pixels(&self, glyph: Glyph) -> ArrayView2<'_, bool> {
+        let x = glyph.x as usize;
+        self.atlas.slice(s![.., x..x + glyph.width as usize])
+    }
+
+    /// Size of `text` drawn at `scale`, as wide as its longest line and one line per `\n`.
+    pub fn measure_text(&self, text: &str, scale: u32) -> Size {
+        let (mut width, mut lines) = (0, 0);
+        for line in text.split('\n') {
+            let line_width = line
+                .chars()
+                .filter_map(|c| self.glyph(c))
+                .map(|glyph| u64::from(glyph.advance))
+                .sum::<u64>();
+            width = width.max(line_width);
+            lines += 1;
+        }
+        let scaled =
+            |value: u64| u32::try_from(value.saturating_mul(u64::from(scale))).unwrap_or(u32::MAX);
+        Size::new(scaled(width), scaled(lines * u64::from(self.line_height())))
+    }
+
+    /// Unscaled bitmap position of every glyph of `text`, relative to its top left corner.
+    pub(crate) fn layout<'a>(
+        &'a self,
+        text: &'a str,
+    ) -> impl Iterator<Item = (i64, i64, Glyph)> + 'a {
+        let line_height = i64::from(self.line_height());
+        text.split('\n').enumerate().flat_map(move |(line, text)| {
+            text.chars()
+                .filter_map(|c| self.glyph(c))
+                .scan(0, |x, glyph| {
+                    let pen = *x;
+                    *x += i64::from(glyph.advance);
+                    Some((pen + i64::from(glyph.left), glyph))
+                })
+                .map(move |(x, glyph)| (x, line as i64 * line_height, glyph))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_glyphs() {
+        let font = Font::builtin();
+        assert_eq!(font.line_height(), 8);
+        let glyph = font.glyph('T').unwrap();
+        assert_eq!((glyph.width(), glyph.advance()), (5, 6));
+        let pixels = font.glyph_pixels(glyph);
+        assert_eq!(pixels.dim(), (8, 5));
+        assert_eq!(pixels.row(0).to_vec(), [true; 5]);
+        assert_eq!(pixels.row(1).to_vec(), [false, false, true, false, false]);
+        assert_eq!(pixels.row(7).to_vec(), [false; 5]);
+        assert!(font
+            .glyph_pixels(font.glyph(' ').unwrap())
+            .iter()
+            .all(|&set| !set));
+    }
+
+    #[test]
+    fn fallback_glyph() {
+        let font = Font::builtin();
+        assert_eq!(font.glyph('é'), font.glyph('?'));
+        assert_eq!(font.glyph('\t'), font.glyph('?'));
+    }
+
+    #[test]
+    fn measure_text() {
+        let font = Font::builtin();
+        assert_eq!(font.measure_text("", 1), Size::new(0, 8));
+        assert_eq!(font.measure_text("FPS", 1), Size::new(18, 8));
+        assert_eq!(font.measure_text("FPS: 60\nn", 2), Size::new(84, 32));
+        assert_eq!(
+            font.measure_text("x", u32::MAX),
+            Size::new(u32::MAX, u32::MAX)
+        );
+    }
+
+    #[test]
+    fn layout() {
+        let font = Font::builtin();
+        let pens: Vec<_> = font.layout("ab\nc").map(|(x, y, _)| (x, y)).collect();
+        assert_eq!(pens, [(0, 0), (6, 0), (0, 8)]);
+    }
+}
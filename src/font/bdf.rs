@@ -0,0 +1,268 @@
+use crate::font::{Font, GlyphBitmap};
+use crate::Error;
+use ndarray::Array2;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// Guard against absurd bounding boxes before allocating glyph bitmaps.
+const DIMENSION_MAX: u32 = 1024;
+/// Guard against many large glyphs in a small file, bounds the atlas to 64 MiB.
+const ATLAS_CELLS_MAX: u64 = 1 << 26;
+
+#[derive(Default)]
+struct Char {
+    encoding: Option<char>,
+    advance: Option<u32>,
+    bbx: Option<[i32; 4]>,
+}
+
+impl Font {
+    /// Decodes a BDF font. Encodings are taken as Unicode code points, which holds for
+    /// ISO10646 and ISO8859-1 fonts; glyphs without an encoding are skipped.
+    pub fn read_bdf(reader: impl BufRead) -> Result<Self, Error> {
+        let mut lines = reader.lines();
+        let mut next_line = || -> Result<Option<String>, Error> { Ok(lines.next().transpose()?) };
+
+        if !next_line()?.is_some_and(|line| line.starts_with("STARTFONT")) {
+            return Err(Error::InvalidBdf("missing STARTFONT"));
+        }
+        let mut bounding_box = None;
+        let (mut ascent, mut descent) = (None, None);
+        let mut default_advance = None;
+        let mut glyphs = vec![];
+        let mut current: Option<Char> = None;
+        let mut line_height = None;
+        let mut atlas_width = 0;
+        while let Some(line) = next_line()? {
+            let (keyword, args) = line.split_once(' ').unwrap_or((line.as_str(), ""));
+            match (keyword, &mut current) {
+                ("FONTBOUNDINGBOX", None) => bounding_box = Some(numbers::<4>(args)?),
+                ("FONT_ASCENT", None) => ascent = Some(numbers::<1>(args)?[0]),
+                ("FONT_DESCENT", None) => descent = Some(numbers::<1>(args)?[0]),
+                ("DWIDTH", None) => default_advance = Some(advance(args)?),
+                ("STARTCHAR", None) => current = Some(Char::default()),
+                ("ENCODING", Some(current)) => {
+                    // -1 followed by the code point marks a non-standard encoding
+                    let encoding = match numbers::<1>(args)? {
+                        [-1] => args.split_ascii_whitespace().nth(1).map(str::parse),
+                        [encoding] => Some(Ok(encoding)),
+                    };
+                    current.encoding = encoding
+                        .transpose()
+                        .map_err(|_| Error::InvalidBdf("invalid number"))?
+                        .and_then(|encoding: i32| u32::try_from(encoding).ok())
+                        .and_then(char::from_u32);
+                }
+                ("DWIDTH", Some(current)) => current.advance = Some(advance(args)?),
+                ("BBX", Some(current)) => current.bbx = Some(numbers::<4>(args)?),
+                ("BITMAP", Some(glyph)) => {
+                    let [width, height, left, bottom] = glyph
+                        .bbx
+                        .or(bounding_box)
+                        .ok_or(Error::InvalidBdf("missing BBX"))?;
+                    let (ascent, line_height) = match line_height {
+                        Some(metrics) => metrics,
+                        None => *line_height.insert(metrics(bounding_box, ascent, descent)?),
+                    };
+                    let (width, height) = (dimension(width)?, dimension(height)?);
+                    atlas_width += u64::from(width);
+                    if u64::from(line_height) * atlas_width > ATLAS_CELLS_MAX {
+                        return Err(Error::InvalidBdf("font is too large"));
+                    }
+                    let top = i64::from(ascent) - i64::from(bottom) - i64::from(height);
+                    let mut pixels =
+                        Array2::from_elem((line_height as usize, width as usize), false);
+                    for row in 0..i64::from(height) {
+                        let line = next_line()?.ok_or(Error::InvalidBdf("truncated bitmap"))?;
+                        let line = line.trim();
+                        let Ok(y) = usize::try_from(top + row) else {
+                            continue;
+                        };
+                        for x in 0..width as usize {
+                            let digit = line
+                                .as_bytes()
+                                .get(x / 4)
+                                .and_then(|&digit| (digit as char).to_digit(16))
+                                .ok_or(Error::InvalidBdf("invalid bitmap row"))?;
+                            if let Some(pixel) = pixels.get_mut((y, x)) {
+                                *pixel = digit >> (3 - x % 4) & 1 != 0;
+                            }
+                        }
+                    }
+                    if let Some(c) = glyph.encoding {
+                        glyphs.push(GlyphBitmap {
+                            chars: vec![c],
+                            pixels,
+                            left,
+                            advance: glyph
+                                .advance
+                                .or(default_advance)
+                                .unwrap_or(width.saturating_add_signed(left)),
+                        });
+                    }
+                }
+                ("ENDCHAR", Some(_)) => current = None,
+                ("ENDFONT", None) => {
+                    let (_, line_height) = match line_height {
+                        Some(line_height) => line_height,
+                        None => metrics(bounding_box, ascent, descent)?,
+                    };
+                    return Ok(Self::from_glyphs(line_height, glyphs));
+                }
+                ("STARTCHAR" | "ENDFONT", Some(_)) => {
+                    return Err(Error::InvalidBdf("missing ENDCHAR"))
+                }
+                _ => {}
+            }
+        }
+        Err(Error::InvalidBdf("missing ENDFONT"))
+    }
+
+    #[inline]
+    pub fn open_bdf(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::read_bdf(BufReader::new(File::open(path)?))
+    }
+}
+
+/// Ascent and line height, from the font properties or else the font bounding box.
+fn metrics(
+    bounding_box: Option<[i32; 4]>,
+    ascent: Option<i32>,
+    descent: Option<i32>,
+) -> Result<(i32, u32), Error> {
+    let bounding_box = bounding_box.ok_or(Error::InvalidBdf("missing FONTBOUNDINGBOX"))?;
+    let [_, height, _, bottom] = bounding_box;
+    let ascent = ascent.unwrap_or(height.saturating_add(bottom));
+    let descent = descent.unwrap_or(-bottom);
+    let line_height = dimension(ascent.saturating_add(descent))?;
+    Ok((ascent, line_height))
+}
+
+#[inline]
+fn dimension(value: i32) -> Result<u32, Error> {
+    u32::try_from(value)
+        .ok()
+        .filter(|&value| value <= DIMENSION_MAX)
+        .ok_or(Error::InvalidBdf("invalid dimensions"))
+}
+
+#[inline]
+fn advance(args: &str) -> Result<u32, Error> {
+    let [x, _] = numbers::<2>(args)?;
+    u32::try_from(x).map_err(|_| Error::InvalidBdf("negative DWIDTH"))
+}
+
+fn numbers<const N: usize>(args: &str) -> Result<[i32; N], Error> {
+    let mut numbers = args.split_ascii_whitespace().map(str::parse);
+    let mut result = [0; N];
+    for number in &mut result {
+        *number = numbers
+            .next()
+            .and_then(Result::ok)
+            .ok_or(Error::InvalidBdf("invalid number"))?;
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FONT: &str = "\
+STARTFONT 2.1
+FONT -test-fixed-medium-r-normal--4-40-75-75-c-40-iso10646-1
+SIZE 4 75 75
+FONTBOUNDINGBOX 4 4 0 -1
+STARTPROPERTIES 2
+FONT_ASCENT 3
+FONT_DESCENT 1
+ENDPROPERTIES
+CHARS 4
+STARTCHAR A
+ENCODING 65
+SWIDTH 1000 0
+DWIDTH 4 0
+BBX 3 3 0 0
+BITMAP
+40
+A0
+E0
+ENDCHAR
+STARTCHAR uni00E9
+ENCODING 233
+DWIDTH 5 0
+BBX 2 2 1 -1
+BITMAP
+C0
+40
+ENDCHAR
+STARTCHAR unencoded
+ENCODING -1
+BBX 1 1 0 0
+BITMAP
+80
+ENDCHAR
+STARTCHAR private
+ENCODING -1 57344
+BBX 1 1 0 0
+BITMAP
+80
+ENDCHAR
+ENDFONT
+";
+
+    fn rows(font: &Font, c: char) -> Vec<String> {
+        let pixels = font.glyph_pixels(font.glyph(c).unwrap());
+        pixels
+            .rows()
+            .into_iter()
+            .map(|row| row.iter().map(|&set| if set { '#' } else { '.' }).collect())
+            .collect()
+    }
+
+    #[test]
+    fn read() {
+        let font = Font::read_bdf(FONT.as_bytes()).unwrap();
+        assert_eq!(font.line_height(), 4);
+        assert_eq!(font.glyph_count(), 3);
+        assert_eq!(rows(&font, '\u{E000}'), [".", ".", "#", "."]);
+        assert_eq!(rows(&font, 'A'), [".#.", "#.#", "###", "..."]);
+        assert_eq!(rows(&font, 'é'), ["..", "..", "##", ".#"]);
+        let glyph = font.glyph('é').unwrap();
+        assert_eq!((glyph.left(), glyph.advance()), (1, 5));
+        assert_eq!(font.glyph('B'), None);
+        assert_eq!(font.measure_text("Aé", 1).width, 9);
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(matches!(
+            Font::read_bdf(b"STARTFONT 2.1\n".as_slice()),
+            Err(Error::InvalidBdf("missing ENDFONT"))
+        ));
+        assert!(matches!(
+            Font::read_bdf(FONT.replace("BBX 3 3 0 0", "BBX 3 5000 0 0").as_bytes()),
+            Err(Error::InvalidBdf("invalid dimensions"))
+        ));
+        assert!(matches!(
+            Font::read_bdf(FONT.replace("A0\n", "Z0\n").as_bytes()),
+            Err(Error::InvalidBdf("invalid bitmap row"))
+        ));
+        let large = FONT
+            .replace("FONTBOUNDINGBOX 4 4 0 -1", "FONTBOUNDINGBOX 1024 1024 0 0")
+            .replace("FONT_ASCENT 3", "FONT_ASCENT 1023")
+            .replace(
+                "CHARS 4",
+                &"STARTCHAR empty\nBBX 1024 0 0 0\nBITMAP\nENDCHAR\n".repeat(65),
+            );
+        assert!(matches!(
+            Font::read_bdf(large.as_bytes()),
+            Err(Error::InvalidBdf("font is too large"))
+        ));
+        assert!(matches!(
+            Font::read_bdf(b"P6\n".as_slice()),
+            Err(Error::InvalidBdf("missing STARTFONT"))
+        ));
+    }
+}
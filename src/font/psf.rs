@@ -0,0 +1,279 @@
+use crate::font::{Font, GlyphBitmap};
+use crate::Error;
+use ndarray::Array2;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+const PSF1_MAGIC: [u8; 2] = [0x36, 0x04];
+const PSF1_MODE_512: u8 = 0x01;
+const PSF1_MODE_HAS_TAB: u8 = 0x02;
+const PSF1_SEPARATOR: u16 = 0xFFFF;
+const PSF1_START_SEQ: u16 = 0xFFFE;
+
+const PSF2_MAGIC: [u8; 4] = [0x72, 0xB5, 0x4A, 0x86];
+const PSF2_HAS_UNICODE_TABLE: u32 = 0x01;
+const PSF2_SEPARATOR: u8 = 0xFF;
+const PSF2_START_SEQ: u8 = 0xFE;
+
+/// Guards against absurd headers before allocating glyph bitmaps.
+const DIMENSION_MAX: u32 = 1024;
+const GLYPHS_MAX: u32 = 0x11_0000;
+
+impl Font {
+    /// Decodes a PC Screen Font, version 1 or 2.
+    ///
+    /// Glyphs are mapped through the Unicode table of the font; without one glyph `n` is taken
+    /// as code point `n`. Multi-character sequences in the table are skipped.
+    pub fn read_psf(mut reader: impl Read) -> Result<Self, Error> {
+        let mut magic = [0; 2];
+        reader.read_exact(&mut magic)?;
+        if magic == PSF1_MAGIC {
+            let mut header = [0; 2];
+            reader.read_exact(&mut header)?;
+            let [mode, height] = header;
+            let count = if mode & PSF1_MODE_512 != 0 { 512 } else { 256 };
+            let bitmaps = read_bitmaps(&mut reader, count, 8, u32::from(height))?;
+            let chars = if mode & PSF1_MODE_HAS_TAB != 0 {
+                let mut table = vec![];
+                reader.read_to_end(&mut table)?;
+                psf1_chars(&table, count)?
+            } else {
+                identity_chars(count)
+            };
+            return Ok(build(bitmaps, chars, 8, u32::from(height)));
+        }
+
+        let mut rest = [0; 2];
+        reader.read_exact(&mut rest)?;
+        if [magic, rest].concat() != PSF2_MAGIC {
+            return Err(Error::InvalidPsf("unknown magic"));
+        }
+        let mut header = [0; 28];
+        reader.read_exact(&mut header)?;
+        let [_version, header_size, flags, count, glyph_size, height, width] =
+            std::array::from_fn(|i| {
+                u32::from_le_bytes([
+                    header[4 * i],
+                    header[4 * i + 1],
+                    header[4 * i + 2],
+                    header[4 * i + 3],
+                ])
+            });
+        if width == 0 || width > DIMENSION_MAX || height > DIMENSION_MAX || count > GLYPHS_MAX {
+            return Err(Error::InvalidPsf("invalid dimensions"));
+        }
+        if u64::from(glyph_size) != u64::from(height) * u64::from(width.div_ceil(8)) {
+            return Err(Error::InvalidPsf("glyph size does not match dimensions"));
+        }
+        let extra = header_size
+            .checked_sub(32)
+            .ok_or(Error::InvalidPsf("header too small"))?;
+        std::io::copy(
+            &mut (&mut reader).take(u64::from(extra)),
+            &mut std::io::sink(),
+        )?;
+        let bitmaps = read_bitmaps(&mut reader, count as usize, width, height)?;
+        let chars = if flags & PSF2_HAS_UNICODE_TABLE != 0 {
+            let mut table = vec![];
+            reader.read_to_end(&mut table)?;
+            psf2_chars(&table, count as usize)?
+        } else {
+            identity_chars(count as usize)
+        };
+        Ok(build(bitmaps, chars, width, height))
+    }
+
+    #[inline]
+    pub fn open_psf(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::read_psf(BufReader::new(File::open(path)?))
+    }
+}
+
+/// Reads `count` glyphs of `height` rows, each row padded to whole bytes with the leftmost pixel
+/// in the top bit.
+fn read_bitmaps(
+    reader: &mut impl Read,
+    count: usize,
+    width: u32,
+    height: u32,
+) -> Result<Vec<Array2<bool>>, Error> {
+    let row_bytes = width.div_ceil(8) as usize;
+    let mut data = vec![0; row_bytes * height as usize];
+    (0..count)
+        .map(|_| {
+            reader.read_exact(&mut data)?;
+            Ok(Array2::from_shape_fn(
+                (height as usize, width as usize),
+                |(y, x)| data[y * row_bytes + x / 8] >> (7 - x % 8) & 1 != 0,
+            ))
+        })
+        .collect()
+}
+
+fn build(bitmaps: Vec<Array2<bool>>, chars: Vec<Vec<char>>, width: u32, height: u32) -> Font {
+    let glyphs = bitmaps
+        .into_iter()
+        .zip(chars)
+        .map(|(pixels, chars)| GlyphBitmap {
+            chars,
+            pixels,
+            left: 0,
+            advance: width,
+        })
+        .collect();
+    Font::from_glyphs(height, glyphs)
+}
+
+#[inline]
+fn identity_chars(count: usize) -> Vec<Vec<char>> {
+    (0..count as u32)
+        .map(|code| char::from_u32(code).into_iter().collect())
+        .collect()
+}
+
+/// Unicode table of PSF1: little endian UCS-2 values per glyph, terminated by `0xFFFF`.
+fn psf1_chars(table: &[u8], count: usize) -> Result<Vec<Vec<char>>, Error> {
+    let mut values = table
+        .chunks_exact(2)
+        .map(|value| u16::from_le_bytes([value[0], value[1]]));
+    (0..count)
+        .map(|_| {
+            let mut chars = vec![];
+            let mut sequence = false;
+            loop {
+                match values.next() {
+                    None => return Err(Error::InvalidPsf("truncated unicode table")),
+                    Some(PSF1_SEPARATOR) => return Ok(chars),
+                    Some(PSF1_START_SEQ) => sequence = true,
+                    Some(value) if !sequence => chars.extend(char::from_u32(u32::from(value))),
+                    Some(_) => {}
+                }
+            }
+        })
+        .collect()
+}
+
+/// Unicode table of PSF2: UTF-8 characters per glyph, terminated by `0xFF`.
+fn psf2_chars(table: &[u8], count: usize) -> Result<Vec<Vec<char>>, Error> {
+    let mut entries = table.split(|&byte| byte == PSF2_SEPARATOR);
+    (0..count)
+        .map(|_| {
+            let entry = entries
+                .next()
+                .ok_or(Error::InvalidPsf("truncated unicode table"))?;
+            let singles = entry
+                .split(|&byte| byte == PSF2_START_SEQ)
+                .next()
+                .unwrap_or_default();
+            let singles = std::str::from_utf8(singles)
+                .map_err(|_| Error::InvalidPsf("invalid UTF-8 in unicode table"))?;
+            Ok(singles.chars().collect())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Glyph `n` is a horizontal bar in row `n`.
+    fn bars(count: usize, height: usize, row_bytes: usize) -> Vec<u8> {
+        (0..count)
+            .flat_map(|glyph| {
+                (0..height * row_bytes).map(move |byte| {
+                    if byte / row_bytes == glyph % height {
+                        0xFF
+                    } else {
+                        0x00
+                    }
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn read_psf1() {
+        let mut data = vec![0x36, 0x04, PSF1_MODE_HAS_TAB, 4];
+        data.extend(bars(256, 4, 1));
+        for glyph in 0..256u16 {
+            let values: &[u16] = match glyph {
+                1 => &[0x41, 0x391, PSF1_START_SEQ, 0x41, 0x301],
+                2 => &[0xFFFD],
+                _ => &[],
+            };
+            for value in values.iter().chain(&[PSF1_SEPARATOR]) {
+                data.extend(value.to_le_bytes());
+            }
+        }
+        let font = Font::read_psf(data.as_slice()).unwrap();
+        assert_eq!((font.line_height(), font.glyph_count()), (4, 256));
+        let glyph = font.glyph('Α').unwrap();
+        assert_eq!(font.glyph('A'), Some(glyph));
+        assert_eq!((glyph.width(), glyph.advance()), (8, 8));
+        assert_eq!(font.glyph_pixels(glyph).row(1).to_vec(), [true; 8]);
+        assert_eq!(
+            font.glyph_pixels(font.glyph('\u{301}').unwrap())
+                .row(2)
+                .to_vec(),
+            [true; 8]
+        );
+    }
+
+    #[test]
+    fn read_psf2() {
+        let mut data = PSF2_MAGIC.to_vec();
+        for value in [0, 36, PSF2_HAS_UNICODE_TABLE, 2, 6, 3, 10, 0] {
+            data.extend(u32::to_le_bytes(value));
+        }
+        data.extend(bars(2, 3, 2));
+        data.extend("a".as_bytes());
+        data.push(PSF2_SEPARATOR);
+        data.extend("ж".as_bytes());
+        data.push(PSF2_START_SEQ);
+        data.extend("e\u{301}".as_bytes());
+        data.push(PSF2_SEPARATOR);
+        let font = Font::read_psf(data.as_slice()).unwrap();
+        assert_eq!((font.line_height(), font.glyph_count()), (3, 2));
+        let glyph = font.glyph('ж').unwrap();
+        assert_eq!((glyph.width(), glyph.advance()), (10, 10));
+        assert_eq!(font.glyph_pixels(glyph).row(1).to_vec(), [true; 10]);
+        assert_eq!(font.glyph('e'), None);
+        assert_eq!(font.measure_text("aж", 1), crate::Size::new(20, 3));
+    }
+
+    #[test]
+    fn read_psf2_without_table() {
+        let mut data = PSF2_MAGIC.to_vec();
+        for value in [0, 32, 0, 3, 1, 1, 1] {
+            data.extend(u32::to_le_bytes(value));
+        }
+        data.extend([0x80, 0x00, 0x80]);
+        let font = Font::read_psf(data.as_slice()).unwrap();
+        let pixels = |c| font.glyph_pixels(font.glyph(c).unwrap())[(0, 0)];
+        assert_eq!(
+            [pixels('\0'), pixels('\u{1}'), pixels('\u{2}')],
+            [true, false, true]
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(matches!(
+            Font::read_psf([0x00, 0x01, 0x02, 0x03].as_slice()),
+            Err(Error::InvalidPsf("unknown magic"))
+        ));
+        let mut data = PSF2_MAGIC.to_vec();
+        for value in [0, 32, 0, 1, 7, 3, 10] {
+            data.extend(u32::to_le_bytes(value));
+        }
+        assert!(matches!(
+            Font::read_psf(data.as_slice()),
+            Err(Error::InvalidPsf("glyph size does not match dimensions"))
+        ));
+        assert!(matches!(
+            Font::read_psf([0x36, 0x04, 0x00, 0x08, 0x00].as_slice()),
+            Err(Error::Io(_))
+        ));
+    }
+}
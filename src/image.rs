@@ -0,0 +1,132 @@
+#[cfg(feature = "bmp")]
+mod bmp;
+mod gif;
+mod netpbm;
+#[cfg(feature = "png")]
+mod png;
+mod qoi;
+mod y4m;
+
+pub use gif::{GifOptions, GifWriter};
+pub use netpbm::PpmStream;
+pub use y4m::{Chroma, Y4mWriter};
+
+use crate::{Canvas, Error};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+/// Splits a `0RGB` pixel into its `[R, G, B]` bytes.
+#[inline]
+pub(crate) fn to_rgb(pixel: u32) -> [u8; 3] {
+    let [_, r, g, b] = pixel.to_be_bytes();
+    [r, g, b]
+}
+
+/// Packs `[R, G, B]` bytes into a `0RGB` pixel.
+#[inline]
+pub(crate) fn from_rgb([r, g, b]: [u8; 3]) -> u32 {
+    u32::from_be_bytes([0, r, g, b])
+}
+
+impl Canvas {
+    /// Decodes an image of any supported format, detected by its signature.
+    pub fn read_image(mut reader: impl BufRead) -> Result<Self, Error> {
+        let signature = reader.fill_buf()?;
+        match signature {
+            #[cfg(feature = "png")]
+            [0x89, b'P', b'N', b'G', ..] => Self::read_png(reader),
+            #[cfg(feature = "bmp")]
+            [b'B', b'M', ..] => Self::read_bmp(reader),
+            [b'P', b'5' | b'6' | b'7', ..] => Self::read_netpbm(reader),
+            [b'q', b'o', b'i', b'f', ..] => Self::read_qoi(reader),
+            _ => Err(Error::UnknownImageFormat),
+        }
+    }
+
+    #[inline]
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::read_image(BufReader::new(File::open(path)?))
+    }
+
+    /// Encodes the canvas in the format given by the extension of `path`.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase);
+        let write: fn(&Self, &mut BufWriter<File>) -> Result<(), Error> = match extension.as_deref()
+        {
+            #[cfg(feature = "png")]
+            Some("png") => |canvas, writer| canvas.write_png(writer),
+            Some("ppm") => |canvas, writer| canvas.write_ppm(writer),
+            Some("pgm") => |canvas, writer| canvas.write_pgm(writer),
+            Some("pam") => |canvas, writer| canvas.write_pam(writer),
+            Some("qoi") => |canvas, writer| canvas.write_qoi(writer),
+            _ => return Err(Error::UnknownImageFormat),
+        };
+        let mut writer = BufWriter::new(File::create(path)?);
+        write(self, &mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+
+    #[test]
+    fn rgb_round_trip() {
+        assert_eq!(to_rgb(0x00123456), [0x12, 0x34, 0x56]);
+        assert_eq!(from_rgb([0x12, 0x34, 0x56]), 0x00123456);
+    }
+
+    #[test]
+    fn to_rgb_ignores_top_byte() {
+        assert_eq!(to_rgb(0xFF123456), [0x12, 0x34, 0x56]);
+    }
+
+    #[test]
+    fn read_image_detects_format() {
+        let canvas = Canvas::from_pixels(array![[0x123456, 0xABCDEF]]);
+        let mut ppm = vec![];
+        canvas.write_ppm(&mut ppm).unwrap();
+        let mut qoi = vec![];
+        canvas.write_qoi(&mut qoi).unwrap();
+        for data in [ppm, qoi] {
+            assert_eq!(
+                Canvas::read_image(data.as_slice()).unwrap().pixels(),
+                canvas.pixels()
+            );
+        }
+    }
+
+    #[test]
+    fn read_image_unknown_format() {
+        assert!(matches!(
+            Canvas::read_image(b"GIF89a".as_slice()),
+            Err(Error::UnknownImageFormat)
+        ));
+    }
+
+    #[test]
+    fn save_and_open() {
+        let canvas = Canvas::from_pixels(array![[0x123456], [0xABCDEF]]);
+        let path = std::env::temp_dir().join(format!("verle-save-{}.QOI", std::process::id()));
+        canvas.save(&path).unwrap();
+        let opened = Canvas::open(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(opened.unwrap().pixels(), canvas.pixels());
+    }
+
+    #[test]
+    fn save_unknown_format() {
+        assert!(matches!(
+            Canvas::from_pixels(array![[0]]).save("canvas.gif"),
+            Err(Error::UnknownImageFormat)
+        ));
+    }
+}
@@ -0,0 +1,370 @@
+use crate::image::from_rgb;
+use crate::{Canvas, Error};
+use ndarray::Array2;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+const FILE_HEADER_SIZE: u32 = 14;
+const CORE_HEADER_SIZE: u32 = 12;
+const INFO_HEADER_SIZE: u32 = 40;
+
+/// Guard against absurd header dimensions, like the QOI decoder's.
+const PIXELS_MAX: u64 = 400_000_000;
+
+const BI_RGB: u32 = 0;
+const BI_BITFIELDS: u32 = 3;
+const BI_ALPHABITFIELDS: u32 = 6;
+
+/// A channel mask as used by `BI_BITFIELDS` images.
+#[derive(Debug, Copy, Clone)]
+struct Mask(u32);
+
+impl Mask {
+    #[inline]
+    fn extract(self, value: u32) -> u8 {
+        if self.0 == 0 {
+            return 0;
+        }
+        let max = self.0 >> self.0.trailing_zeros();
+        let channel = (value & self.0) >> self.0.trailing_zeros();
+        ((u64::from(channel) * 0xFF + u64::from(max) / 2) / u64::from(max)) as u8
+    }
+}
+
+struct Reader<R> {
+    inner: R,
+    position: u32,
+}
+
+impl<R: Read> Reader<R> {
+    #[inline]
+    fn bytes<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        let mut bytes = [0; N];
+        self.inner.read_exact(&mut bytes)?;
+        self.position += N as u32;
+        Ok(bytes)
+    }
+
+    #[inline]
+    fn u16(&mut self) -> Result<u16, Error> {
+        self.bytes().map(u16::from_le_bytes)
+    }
+
+    #[inline]
+    fn u32(&mut self) -> Result<u32, Error> {
+        self.bytes().map(u32::from_le_bytes)
+    }
+
+    #[inline]
+    fn i32(&mut self) -> Result<i32, Error> {
+        self.bytes().map(i32::from_le_bytes)
+    }
+
+    fn skip_to(&mut self, position: u32) -> Result<(), Error> {
+        let len = position
+            .checked_sub(self.position)
+            .ok_or(Error::InvalidBmp("pixel data overlaps headers"))?;
+        let skipped = std::io::copy(
+            &mut (&mut self.inner).take(len.into()),
+            &mut std::io::sink(),
+        )?;
+        if skipped != u64::from(len) {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
+        self.position = position;
+        Ok(())
+    }
+}
+
+impl Canvas {
+    /// Decodes an uncompressed or bitfield-encoded BMP image with 1, 4, 8, 16, 24 or 32 bits
+    /// per pixel. Alpha is discarded.
+    pub fn read_bmp(reader: impl Read) -> Result<Self, Error> {
+        let mut reader = Reader {
+            inner: reader,
+            position: 0,
+        };
+        if &reader.bytes()? != b"BM" {
+            return Err(Error::InvalidBmp("invalid signature"));
+        }
+        let _file_size = reader.u32()?;
+        let _reserved = reader.u32()?;
+        let data_offset = reader.u32()?;
+
+        let header_size = reader.u32()?;
+        let (width, height, bpp, compression, colors_used) = match header_size {
+            CORE_HEADER_SIZE => {
+                let width = reader.u16()?;
+                let height = reader.u16()?;
+                let _planes = reader.u16()?;
+                let bpp = reader.u16()?;
+                (i32::from(width), i32::from(height), bpp, BI_RGB, 0)
+            }
+            INFO_HEADER_SIZE.. => {
+                let width = reader.i32()?;
+                let height = reader.i32()?;
+                let _planes = reader.u16()?;
+                let bpp = reader.u16()?;
+                let compression = reader.u32()?;
+                let _image_size = reader.u32()?;
+                let _resolution: [u8; 8] = reader.bytes()?;
+                let colors_used = reader.u32()?;
+                let _colors_important = reader.u32()?;
+                (width, height, bpp, compression, colors_used)
+            }
+            _ => return Err(Error::InvalidBmp("unsupported header")),
+        };
+        if width <= 0 || height == 0 || height == i32::MIN {
+            return Err(Error::InvalidBmp("invalid dimensions"));
+        }
+        if !matches!(bpp, 1 | 4 | 8 | 16 | 24 | 32) {
+            return Err(Error::InvalidBmp("unsupported bit depth"));
+        }
+        // rows take at most 4 bytes per pixel, so this also bounds the row stride
+        if u64::from(width.unsigned_abs()) * u64::from(height.unsigned_abs()) > PIXELS_MAX {
+            return Err(Error::InvalidBmp("image is too large"));
+        }
+        let top_down = height < 0;
+        let (width, height) = (width as usize, height.unsigned_abs() as usize);
+
+        let masks = match compression {
+            BI_RGB => match bpp {
+                16 => [Mask(0x7C00), Mask(0x03E0), Mask(0x001F)],
+                _ => [Mask(0xFF_0000), Mask(0x00_FF00), Mask(0x00_00FF)],
+            },
+            BI_BITFIELDS | BI_ALPHABITFIELDS if matches!(bpp, 16 | 32) => {
+                // masks follow a plain info header and are the first fields of later headers
+                let masks = [reader.u32()?, reader.u32()?, reader.u32()?].map(Mask);
+                if compression == BI_ALPHABITFIELDS {
+                    let _alpha_mask = reader.u32()?;
+                }
+                masks
+            }
+            _ => return Err(Error::InvalidBmp("unsupported compression")),
+        };
+        let headers_end = FILE_HEADER_SIZE
+            .checked_add(header_size)
+            .ok_or(Error::InvalidBmp("header is too large"))?;
+        reader.skip_to(headers_end.max(reader.position))?;
+
+        let palette = if bpp <= 8 {
+            let entries = match colors_used {
+                0 => 1 << bpp,
+                n if n <= 1 << bpp => n,
+                _ => return Err(Error::InvalidBmp("palette is too large")),
+            };
+            let mut palette = Vec::with_capacity(entries as usize);
+            for _ in 0..entries {
+                palette.push(if header_size == CORE_HEADER_SIZE {
+                    let [b, g, r] = reader.bytes()?;
+                    from_rgb([r, g, b])
+                } else {
+                    let [b, g, r, _] = reader.bytes()?;
+                    from_rgb([r, g, b])
+                });
+            }
+            palette
+        } else {
+            vec![]
+        };
+        reader.skip_to(data_offset)?;
+
+        let stride = (width * usize::from(bpp)).div_ceil(32) * 4;
+        let mut row = vec![0; stride];
+        let mut pixels = Array2::zeros((height, width));
+        for y in 0..height {
+            reader.inner.read_exact(&mut row)?;
+            let y = if top_down { y } else { height - 1 - y };
+            for (x, pixel) in pixels.row_mut(y).iter_mut().enumerate() {
+                *pixel = match bpp {
+                    1 | 4 | 8 => {
+                        let bit = x * usize::from(bpp);
+                        let shift = 8 - usize::from(bpp) - bit % 8;
+                        let index = (row[bit / 8] >> shift) & ((1 << bpp) - 1) as u8;
+                        *palette
+                            .get(usize::from(index))
+                            .ok_or(Error::InvalidBmp("palette index out of range"))?
+                    }
+                    24 => {
+                        let [b, g, r] = [row[x * 3], row[x * 3 + 1], row[x * 3 + 2]];
+                        from_rgb([r, g, b])
+                    }
+                    _ => {
+                        let value = if bpp == 16 {
+                            u32::from(u16::from_le_bytes([row[x * 2], row[x * 2 + 1]]))
+                        } else {
+                            u32::from_le_bytes([
+                                row[x * 4],
+                                row[x * 4 + 1],
+                                row[x * 4 + 2],
+                                row[x * 4 + 3],
+                            ])
+                        };
+                        from_rgb(masks.map(|mask| mask.extract(value)))
+                    }
+                };
+            }
+        }
+        Ok(Canvas::from_pixels(pixels))
+    }
+
+    #[inline]
+    pub fn open_bmp(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::read_bmp(BufReader::new(File::open(path)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+
+    fn bmp(header: &[u8], palette: &[u8], data: &[u8]) -> Vec<u8> {
+        let offset = 14 + 4 + header.len() + palette.len();
+        let mut bmp = b"BM".to_vec();
+        bmp.extend_from_slice(&((offset + data.len()) as u32).to_le_bytes());
+        bmp.extend_from_slice(&[0; 4]);
+        bmp.extend_from_slice(&(offset as u32).to_le_bytes());
+        bmp.extend_from_slice(&(header.len() as u32 + 4).to_le_bytes());
+        bmp.extend_from_slice(header);
+        bmp.extend_from_slice(palette);
+        bmp.extend_from_slice(data);
+        bmp
+    }
+
+    fn info_header(width: i32, height: i32, bpp: u16, compression: u32, colors: u32) -> Vec<u8> {
+        let mut header = vec![];
+        header.extend_from_slice(&width.to_le_bytes());
+        header.extend_from_slice(&height.to_le_bytes());
+        header.extend_from_slice(&1u16.to_le_bytes());
+        header.extend_from_slice(&bpp.to_le_bytes());
+        header.extend_from_slice(&compression.to_le_bytes());
+        header.extend_from_slice(&[0; 12]);
+        header.extend_from_slice(&colors.to_le_bytes());
+        header.extend_from_slice(&[0; 4]);
+        header
+    }
+
+    #[test]
+    fn read_24_bit_bottom_up() {
+        let data = bmp(
+            &info_header(2, 2, 24, BI_RGB, 0),
+            &[],
+            &[
+                0x56, 0x34, 0x12, 0xFF, 0xFF, 0xFF, 0, 0, // bottom row, padded
+                0x00, 0x00, 0xFF, 0x00, 0xFF, 0x00, 0, 0, // top row, padded
+            ],
+        );
+        assert_eq!(
+            Canvas::read_bmp(data.as_slice()).unwrap().pixels(),
+            array![[0xFF0000, 0x00FF00], [0x123456, 0xFFFFFF]]
+        );
+    }
+
+    #[test]
+    fn read_32_bit_top_down() {
+        let data = bmp(
+            &info_header(1, -2, 32, BI_RGB, 0),
+            &[],
+            &[0x03, 0x02, 0x01, 0xFF, 0x06, 0x05, 0x04, 0x00],
+        );
+        assert_eq!(
+            Canvas::read_bmp(data.as_slice()).unwrap().pixels(),
+            array![[0x010203], [0x040506]]
+        );
+    }
+
+    #[test]
+    fn read_16_bit_bitfields() {
+        let mut header = info_header(2, 1, 16, BI_BITFIELDS, 0);
+        for mask in [0xF800u32, 0x07E0, 0x001F] {
+            header.extend_from_slice(&mask.to_le_bytes());
+        }
+        let data = bmp(&header, &[], &[0x00, 0xF8, 0x1F, 0x00]);
+        assert_eq!(
+            Canvas::read_bmp(data.as_slice()).unwrap().pixels(),
+            array![[0xFF0000, 0x0000FF]]
+        );
+    }
+
+    #[test]
+    fn read_4_bit_palette() {
+        let data = bmp(
+            &info_header(3, 1, 4, BI_RGB, 2),
+            &[0x00, 0x00, 0xFF, 0x00, 0xFF, 0x00, 0x00, 0x00],
+            &[0x10, 0x10, 0x00, 0x00],
+        );
+        assert_eq!(
+            Canvas::read_bmp(data.as_slice()).unwrap().pixels(),
+            array![[0x0000FF, 0xFF0000, 0x0000FF]]
+        );
+    }
+
+    #[test]
+    fn read_1_bit_core_header() {
+        let mut header = vec![];
+        for value in [9u16, 1, 1, 1] {
+            header.extend_from_slice(&value.to_le_bytes());
+        }
+        let data = bmp(
+            &header,
+            &[0x00, 0x00, 0x00, 0xFF, 0xFF, 0xFF],
+            &[0b1010_0000, 0b1000_0000, 0, 0],
+        );
+        assert_eq!(
+            Canvas::read_bmp(data.as_slice()).unwrap().pixels(),
+            array![[
+                0xFFFFFF, 0x000000, 0xFFFFFF, 0x000000, 0x000000, 0x000000, 0x000000, 0x000000,
+                0xFFFFFF
+            ]]
+        );
+    }
+
+    #[test]
+    fn read_invalid_signature() {
+        assert!(matches!(
+            Canvas::read_bmp(b"BA\0\0\0\0".as_slice()),
+            Err(Error::InvalidBmp("invalid signature"))
+        ));
+    }
+
+    #[test]
+    fn read_rle_is_unsupported() {
+        let data = bmp(&info_header(1, 1, 8, 1, 0), &[], &[]);
+        assert!(matches!(
+            Canvas::read_bmp(data.as_slice()),
+            Err(Error::InvalidBmp("unsupported compression"))
+        ));
+    }
+
+    #[test]
+    fn read_too_large() {
+        for (width, height) in [(i32::MAX, i32::MAX), (i32::MAX, 1), (20_001, -20_000)] {
+            let data = bmp(&info_header(width, height, 32, BI_RGB, 0), &[], &[]);
+            assert!(matches!(
+                Canvas::read_bmp(data.as_slice()),
+                Err(Error::InvalidBmp("image is too large"))
+            ));
+        }
+    }
+
+    #[test]
+    fn read_header_size_overflow() {
+        let mut data = bmp(&info_header(1, 1, 24, BI_RGB, 0), &[], &[0; 4]);
+        data[14..18].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            Canvas::read_bmp(data.as_slice()),
+            Err(Error::InvalidBmp("header is too large"))
+        ));
+    }
+
+    #[test]
+    fn read_truncated() {
+        let data = bmp(&info_header(2, 2, 24, BI_RGB, 0), &[], &[0; 8]);
+        assert!(matches!(
+            Canvas::read_bmp(data.as_slice()),
+            Err(Error::Io(_))
+        ));
+    }
+}
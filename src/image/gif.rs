@@ -0,0 +1,555 @@
+use crate::image::{from_rgb, to_rgb};
+use crate::target::RenderTarget;
+use crate::{Canvas, Error, Pos, Rect, Size};
+use ndarray::{s, Array2, ArrayView2};
+use std::collections::HashMap;
+use std::io::Write;
+
+const MAX_CODE_SIZE: u8 = 12;
+const MAX_CODES: u16 = 1 << MAX_CODE_SIZE;
+const MAX_COLORS: usize = 256;
+/// Browsers and most viewers play shorter delays as 10 hundredths.
+const MIN_DELAY: u16 = 2;
+
+#[derive(Eq, PartialEq, Debug, Copy, Clone)]
+pub struct GifOptions {
+    /// Delay after every frame in hundredths of a second, at least 2 as viewers slow down
+    /// shorter delays.
+    pub delay: u16,
+    /// Loop the animation forever.
+    pub repeat: bool,
+    /// Encode only the bounding box of pixels changed since the previous frame.
+    pub only_changed: bool,
+}
+
+impl Default for GifOptions {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            delay: 4,
+            repeat: true,
+            only_changed: false,
+        }
+    }
+}
+
+/// Writes canvases as frames of an animated GIF. Every frame gets its own palette, reduced by
+/// median cut when the frame has more than 256 colors.
+///
+/// The GIF header is written with the first frame, which also fixes the frame size.
+pub struct GifWriter<W: Write> {
+    writer: W,
+    options: GifOptions,
+    previous: Option<Array2<u32>>,
+}
+
+impl<W: Write> GifWriter<W> {
+    #[inline]
+    pub fn new(writer: W, options: GifOptions) -> Self {
+        Self {
+            writer,
+            options,
+            previous: None,
+        }
+    }
+
+    pub fn write_frame(&mut self, canvas: &Canvas) -> Result<(), Error> {
+        let size = canvas.size();
+        let pixels = canvas.pixels();
+        let region = match &self.previous {
+            None => {
+                self.write_header(size)?;
+                Some(canvas.rect())
+            }
+            Some(previous) if previous.dim() != pixels.dim() => {
+                let (height, width) = previous.dim();
+                return Err(Error::FrameSizeMismatch {
+                    expected: Size::new(width as u32, height as u32),
+                    actual: size,
+                });
+            }
+            Some(previous) if self.options.only_changed => changed_region(previous, pixels),
+            Some(_) => Some(canvas.rect()),
+        };
+        // an unchanged frame still needs a pixel to carry its delay
+        let region = region.unwrap_or_else(|| {
+            Rect::try_new_size(Pos::new(0, 0), Size::new(1, 1)).unwrap_or_else(|_| unreachable!())
+        });
+        let view = pixels.slice(s![
+            region.top()..region.bottom(),
+            region.left()..region.right()
+        ]);
+        self.write_image(region, view)?;
+        match &mut self.previous {
+            Some(previous) => previous.assign(pixels),
+            None => self.previous = Some(pixels.clone()),
+        }
+        Ok(())
+    }
+
+    /// Writes the GIF trailer and returns the underlying writer.
+    pub fn finish(mut self) -> Result<W, Error> {
+        self.write_trailer()?;
+        Ok(self.writer)
+    }
+
+    /// Ends the current animation, the next frame starts a new one.
+    fn write_trailer(&mut self) -> Result<(), Error> {
+        if self.previous.take().is_some() {
+            self.writer.write_all(&[0x3B])?;
+        }
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    fn write_header(&mut self, size: Size) -> Result<(), Error> {
+        let (Ok(width), Ok(height)) = (u16::try_from(size.width), u16::try_from(size.height))
+        else {
+            return Err(Error::GifTooLarge(size));
+        };
+        self.writer.write_all(b"GIF89a")?;
+        self.writer.write_all(&width.to_le_bytes())?;
+        self.writer.write_all(&height.to_le_bytes())?;
+        // no global color table, background color 0, square pixels
+        self.writer.write_all(&[0x00, 0x00, 0x00])?;
+        if self.options.repeat {
+            self.writer
+                .write_all(b"\x21\xFF\x0BNETSCAPE2.0\x03\x01\x00\x00\x00")?;
+        }
+        Ok(())
+    }
+
+    fn write_image(&mut self, region: Rect, pixels: ArrayView2<u32>) -> Result<(), Error> {
+        let mut palette = Palette::new(pixels);
+        let table_bits = palette.table_bits();
+
+        // graphic control extension: keep the previous frame under this one
+        self.writer.write_all(&[0x21, 0xF9, 0x04, 0x01 << 2])?;
+        let delay = self.options.delay.max(MIN_DELAY);
+        self.writer.write_all(&delay.to_le_bytes())?;
+        self.writer.write_all(&[0x00, 0x00])?;
+
+        self.writer.write_all(&[0x2C])?;
+        let (left, top) = (region.left() as u16, region.top() as u16);
+        for value in [left, top, region.width() as u16, region.height() as u16] {
+            self.writer.write_all(&value.to_le_bytes())?;
+        }
+        self.writer.write_all(&[0x80 | (table_bits - 1)])?;
+        for index in 0..1 << table_bits {
+            let color = palette.colors.get(index).copied().unwrap_or_default();
+            self.writer.write_all(&to_rgb(color))?;
+        }
+
+        let min_code_size = table_bits.max(2);
+        self.writer.write_all(&[min_code_size])?;
+        let indices: Vec<u8> = pixels.iter().map(|&pixel| palette.index(pixel)).collect();
+        let data = lzw_encode(&indices, min_code_size);
+        for block in data.chunks(0xFF) {
+            self.writer.write_all(&[block.len() as u8])?;
+            self.writer.write_all(block)?;
+        }
+        self.writer.write_all(&[0x00])?;
+        Ok(())
+    }
+}
+
+impl<W: Write> RenderTarget for GifWriter<W> {
+    #[inline]
+    fn present(&mut self, canvas: &Canvas) -> Result<(), Error> {
+        self.write_frame(canvas)
+    }
+
+    #[inline]
+    fn close(&mut self) -> Result<(), Error> {
+        self.write_trailer()
+    }
+}
+
+fn changed_region(previous: &Array2<u32>, pixels: &Array2<u32>) -> Option<Rect> {
+    let mut bounds: Option<(usize, usize, usize, usize)> = None;
+    for (((y, x), previous), pixel) in previous.indexed_iter().zip(pixels) {
+        if previous != pixel {
+            bounds = Some(match bounds {
+                None => (x, y, x + 1, y + 1),
+                Some((left, top, right, bottom)) => {
+                    (left.min(x), top.min(y), right.max(x + 1), bottom.max(y + 1))
+                }
+            });
+        }
+    }
+    let (left, top, right, bottom) = bounds?;
+    Rect::try_new(
+        Pos::new(left as i32, top as i32),
+        Pos::new(right as i32, bottom as i32),
+    )
+    .ok()
+}
+
+struct Palette {
+    colors: Vec<u32>,
+    /// Index of every color looked up so far, filled on demand for reduced palettes as frames
+    /// repeat their colors heavily.
+    indices: HashMap<u32, u8>,
+}
+
+impl Palette {
+    fn new(pixels: ArrayView2<u32>) -> Self {
+        let mut histogram = HashMap::new();
+        for &pixel in pixels {
+            *histogram.entry(pixel & 0xFF_FFFF).or_insert(0u32) += 1;
+        }
+        let mut colors: Vec<(u32, u32)> = histogram.into_iter().collect();
+        // deterministic output regardless of hash order
+        colors.sort_unstable();
+        if colors.len() <= MAX_COLORS {
+            let indices = colors
+                .iter()
+                .enumerate()
+                .map(|(index, &(color, _))| (color, index as u8))
+                .collect();
+            return Self {
+                colors: colors.into_iter().map(|(color, _)| color).collect(),
+                indices,
+            };
+        }
+
+        Self {
+            colors: median_cut(colors),
+            indices: HashMap::new(),
+        }
+    }
+
+    /// Bits per index of the color table, which GIF requires to be a power of two in size.
+    #[inline]
+    fn table_bits(&self) -> u8 {
+        (usize::BITS - (self.colors.len().max(2) - 1).leading_zeros()) as u8
+    }
+
+    #[inline]
+    fn index(&mut self, pixel: u32) -> u8 {
+        let color = pixel & 0xFF_FFFF;
+        *self
+            .indices
+            .entry(color)
+            .or_insert_with(|| nearest(&self.colors, color))
+    }
+}
+
+/// Weighted colors of one median cut box with their widest channel range and that channel,
+/// computed once when the box is created.
+struct ColorBox {
+    colors: Vec<(u32, u32)>,
+    widest: (u8, usize),
+}
+
+impl ColorBox {
+    fn new(colors: Vec<(u32, u32)>) -> Self {
+        let mut low = [u8::MAX; 3];
+        let mut high = [0; 3];
+        for &(color, _) in &colors {
+            for (channel, value) in to_rgb(color).into_iter().enumerate() {
+                low[channel] = low[channel].min(value);
+                high[channel] = high[channel].max(value);
+            }
+        }
+        let widest = (0..3)
+            .map(|channel| (high[channel].saturating_sub(low[channel]), channel))
+            .max()
+            .unwrap_or_default();
+        Self { colors, widest }
+    }
+}
+
+/// Splits the weighted color set into at most 256 boxes, each time halving the box with the
+/// widest channel range at its weighted median, and returns the mean color of every box.
+fn median_cut(colors: Vec<(u32, u32)>) -> Vec<u32> {
+    let mut boxes = vec![ColorBox::new(colors)];
+    while boxes.len() < MAX_COLORS {
+        let Some((index, channel)) = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, color_box)| color_box.colors.len() > 1)
+            .max_by_key(|(_, color_box)| color_box.widest)
+            .map(|(index, color_box)| (index, color_box.widest.1))
+        else {
+            break;
+        };
+        let mut colors = boxes.swap_remove(index).colors;
+        colors.sort_unstable_by_key(|&(color, _)| to_rgb(color)[channel]);
+        let half = colors
+            .iter()
+            .map(|&(_, count)| u64::from(count))
+            .sum::<u64>()
+            / 2;
+        let mut acc = 0;
+        let split = colors
+            .iter()
+            .position(|&(_, count)| {
+                acc += u64::from(count);
+                acc > half
+            })
+            .unwrap_or_default()
+            .clamp(1, colors.len() - 1);
+        let upper = colors.split_off(split);
+        boxes.push(ColorBox::new(colors));
+        boxes.push(ColorBox::new(upper));
+    }
+
+    boxes
+        .iter()
+        .map(|ColorBox { colors, .. }| {
+            let total: u64 = colors.iter().map(|&(_, count)| u64::from(count)).sum();
+            let mut sum = [0u64; 3];
+            for &(color, count) in colors {
+                for (sum, channel) in sum.iter_mut().zip(to_rgb(color)) {
+                    *sum += u64::from(channel) * u64::from(count);
+                }
+            }
+            from_rgb(sum.map(|sum| ((sum + total / 2) / total) as u8))
+        })
+        .collect()
+}
+
+fn nearest(palette: &[u32], color: u32) -> u8 {
+    let [r, g, b] = to_rgb(color).map(i32::from);
+    palette
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, &candidate)| {
+            let [pr, pg, pb] = to_rgb(candidate).map(i32::from);
+            (pr - r).pow(2) + (pg - g).pow(2) + (pb - b).pow(2)
+        })
+        .map_or(0, |(index, _)| index as u8)
+}
+
+/// Packs variable-width codes LSB first, as GIF requires.
+struct BitWriter {
+    data: Vec<u8>,
+    buffer: u32,
+    bits: u8,
+}
+
+impl BitWriter {
+    #[inline]
+    fn write(&mut self, code: u16, width: u8) {
+        self.buffer |= u32::from(code) << self.bits;
+        self.bits += width;
+        while self.bits >= 8 {
+            self.data.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    #[inline]
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.data.push(self.buffer as u8);
+        }
+        self.data
+    }
+}
+
+fn lzw_encode(indices: &[u8], min_code_size: u8) -> Vec<u8> {
+    let clear_code = 1 << min_code_size;
+    let end_code = clear_code + 1;
+    let mut output = BitWriter {
+        data: vec![],
+        buffer: 0,
+        bits: 0,
+    };
+    let mut dictionary: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut next_code = end_code + 1;
+    let mut width = min_code_size + 1;
+    output.write(clear_code, width);
+
+    let mut indices = indices.iter().copied();
+    let Some(first) = indices.next() else {
+        output.write(end_code, width);
+        return output.finish();
+    };
+    let mut prefix = u16::from(first);
+    for index in indices {
+        if let Some(&code) = dictionary.get(&(prefix, index)) {
+            prefix = code;
+            continue;
+        }
+        output.write(prefix, width);
+        if next_code == MAX_CODES {
+            output.write(clear_code, width);
+            dictionary.clear();
+            next_code = end_code + 1;
+            width = min_code_size + 1;
+        } else {
+            dictionary.insert((prefix, index), next_code);
+            next_code += 1;
+            // the decoder adds its entries one code later, so it widens one code later too
+            if next_code > 1 << width && width < MAX_CODE_SIZE {
+                width += 1;
+            }
+        }
+        prefix = u16::from(index);
+    }
+    output.write(prefix, width);
+    output.write(end_code, width);
+    output.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+
+    fn decode(data: &[u8]) -> Vec<(gif::Frame<'static>, Vec<u32>)> {
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::RGBA);
+        let mut decoder = options.read_info(data).unwrap();
+        let mut frames = vec![];
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            let pixels = frame
+                .buffer
+                .chunks_exact(4)
+                .map(|rgba| from_rgb([rgba[0], rgba[1], rgba[2]]))
+                .collect();
+            frames.push((frame.clone(), pixels));
+        }
+        frames
+    }
+
+    #[test]
+    fn lzw_small() {
+        // 3 bit codes: clear, 0, 0 as the new code 6, 1, end
+        assert_eq!(lzw_encode(&[0, 0, 0, 1], 2), [0b1000_0100, 0b0101_0011]);
+    }
+
+    #[test]
+    fn round_trip_frames() {
+        let first = Canvas::from_pixels(array![
+            [0xFF0000, 0x00FF00, 0x0000FF],
+            [0x000000, 0xFFFFFF, 0x123456]
+        ]);
+        let mut second = Canvas::from_pixels(first.pixels().clone());
+        second.draw_dot(Pos::new(1, 1), 0xABCDEF);
+
+        let mut writer = GifWriter::new(
+            vec![],
+            GifOptions {
+                delay: 7,
+                ..Default::default()
+            },
+        );
+        writer.write_frame(&first).unwrap();
+        writer.write_frame(&second).unwrap();
+        let frames = decode(&writer.finish().unwrap());
+
+        assert_eq!(frames.len(), 2);
+        for ((frame, pixels), canvas) in frames.iter().zip([&first, &second]) {
+            assert_eq!(frame.delay, 7);
+            assert_eq!((frame.width, frame.height), (3, 2));
+            assert_eq!(pixels, &canvas.pixels().iter().copied().collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn short_delay_is_raised() {
+        let mut writer = GifWriter::new(
+            vec![],
+            GifOptions {
+                delay: 1,
+                ..Default::default()
+            },
+        );
+        writer
+            .write_frame(&Canvas::new(0, Size::new(1, 1)))
+            .unwrap();
+        assert_eq!(decode(&writer.finish().unwrap())[0].0.delay, MIN_DELAY);
+    }
+
+    #[test]
+    fn only_changed_regions() {
+        let first = Canvas::new(0x000000, Size::new(4, 4));
+        let mut second = Canvas::new(0x000000, Size::new(4, 4));
+        second.draw_dot(Pos::new(1, 2), 0xFF0000);
+        second.draw_dot(Pos::new(2, 3), 0x00FF00);
+
+        let mut writer = GifWriter::new(
+            vec![],
+            GifOptions {
+                only_changed: true,
+                ..Default::default()
+            },
+        );
+        writer.write_frame(&first).unwrap();
+        writer.write_frame(&second).unwrap();
+        writer.write_frame(&second).unwrap();
+        let frames = decode(&writer.finish().unwrap());
+
+        assert_eq!(frames.len(), 3);
+        let (frame, pixels) = &frames[1];
+        assert_eq!(
+            (frame.left, frame.top, frame.width, frame.height),
+            (1, 2, 2, 2)
+        );
+        assert_eq!(frame.dispose, gif::DisposalMethod::Keep);
+        assert_eq!(pixels, &[0xFF0000, 0x000000, 0x000000, 0x00FF00]);
+        let (frame, pixels) = &frames[2];
+        assert_eq!(
+            (frame.left, frame.top, frame.width, frame.height),
+            (0, 0, 1, 1)
+        );
+        assert_eq!(pixels, &[0x000000]);
+    }
+
+    #[test]
+    fn long_data_resets_dictionary() {
+        let canvas = Canvas::from_pixels(Array2::from_shape_fn((64, 256), |(y, x)| {
+            from_rgb([((x * 7 + y * y * 13) % 256) as u8, 0, 0x80])
+        }));
+        let mut writer = GifWriter::new(vec![], Default::default());
+        writer.write_frame(&canvas).unwrap();
+        let frames = decode(&writer.finish().unwrap());
+        assert_eq!(
+            frames[0].1,
+            canvas.pixels().iter().copied().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn median_cut_many_colors() {
+        let canvas = Canvas::from_pixels(Array2::from_shape_fn((64, 64), |(y, x)| {
+            from_rgb([(x * 4) as u8, (y * 4) as u8, 0x80])
+        }));
+        let mut writer = GifWriter::new(vec![], Default::default());
+        writer.write_frame(&canvas).unwrap();
+        let frames = decode(&writer.finish().unwrap());
+        for (&decoded, &original) in frames[0].1.iter().zip(canvas.pixels()) {
+            let distance = to_rgb(decoded)
+                .iter()
+                .zip(to_rgb(original))
+                .map(|(&a, b)| a.abs_diff(b))
+                .max();
+            assert!(distance <= Some(8), "{decoded:06X} vs {original:06X}");
+        }
+    }
+
+    #[test]
+    fn frame_size_mismatch() {
+        let mut writer = GifWriter::new(vec![], Default::default());
+        writer
+            .write_frame(&Canvas::new(0, Size::new(1, 1)))
+            .unwrap();
+        assert!(matches!(
+            writer.write_frame(&Canvas::new(0, Size::new(2, 1))),
+            Err(Error::FrameSizeMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn too_large() {
+        let mut writer = GifWriter::new(vec![], Default::default());
+        assert!(matches!(
+            writer.write_frame(&Canvas::new(0, Size::new(0x1_0000, 0))),
+            Err(Error::GifTooLarge(_))
+        ));
+    }
+}
@@ -0,0 +1,395 @@
+use crate::image::{from_rgb, to_rgb};
+use crate::target::RenderTarget;
+use crate::{Canvas, Error, Size};
+use ndarray::Array2;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+/// Guard against absurd header dimensions, like the QOI decoder's.
+const PIXELS_MAX: u64 = 400_000_000;
+
+impl Canvas {
+    /// Encodes the canvas as a binary PPM (`P6`) image.
+    pub fn write_ppm(&self, mut writer: impl Write) -> Result<(), Error> {
+        let size = self.size();
+        write!(writer, "P6\n{} {}\n255\n", size.width, size.height)?;
+        write_samples(&mut writer, self, to_rgb)
+    }
+
+    /// Encodes the canvas as a binary PGM (`P5`) image using BT.601 luma.
+    pub fn write_pgm(&self, mut writer: impl Write) -> Result<(), Error> {
+        let size = self.size();
+        write!(writer, "P5\n{} {}\n255\n", size.width, size.height)?;
+        write_samples(&mut writer, self, |pixel| [luma(pixel)])
+    }
+
+    /// Encodes the canvas as a PAM (`P7`) image with `RGB` tuple type.
+    pub fn write_pam(&self, mut writer: impl Write) -> Result<(), Error> {
+        let size = self.size();
+        write!(
+            writer,
+            "P7\nWIDTH {}\nHEIGHT {}\nDEPTH 3\nMAXVAL 255\nTUPLTYPE RGB\nENDHDR\n",
+            size.width, size.height
+        )?;
+        write_samples(&mut writer, self, to_rgb)
+    }
+
+    #[inline]
+    pub fn save_ppm(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_ppm(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Decodes exactly one binary Netpbm image (`P5`, `P6` or `P7`) from `reader`.
+    ///
+    /// Nothing past the image raster is consumed, so consecutive calls read consecutive frames
+    /// of a stream.
+    pub fn read_netpbm(mut reader: impl BufRead) -> Result<Self, Error> {
+        let header = Header::read(&mut reader)?;
+        let bytes_per_sample = if header.maxval > 0xFF { 2 } else { 1 };
+        let mut row = vec![0; header.size.width as usize * header.depth * bytes_per_sample];
+        let mut pixels = Array2::zeros((header.size.height as usize, header.size.width as usize));
+        for mut pixels_row in pixels.rows_mut() {
+            reader.read_exact(&mut row)?;
+            let mut samples = row.chunks_exact(bytes_per_sample).map(|sample| {
+                let value = sample
+                    .iter()
+                    .fold(0, |acc, &byte| (acc << 8) | u32::from(byte));
+                if value > header.maxval {
+                    return Err(Error::InvalidNetpbm("sample exceeds maxval"));
+                }
+                Ok(((value * 0xFF + header.maxval / 2) / header.maxval) as u8)
+            });
+            for pixel in pixels_row.iter_mut() {
+                let mut tuple = [0; 4];
+                for sample in &mut tuple[..header.depth] {
+                    *sample = samples.next().unwrap_or(Ok(0))?;
+                }
+                *pixel = match header.depth {
+                    1 | 2 => from_rgb([tuple[0]; 3]),
+                    _ => from_rgb([tuple[0], tuple[1], tuple[2]]),
+                };
+            }
+        }
+        Ok(Canvas::from_pixels(pixels))
+    }
+
+    #[inline]
+    pub fn open_netpbm(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::read_netpbm(BufReader::new(File::open(path)?))
+    }
+}
+
+/// Writes consecutive canvases as a stream of binary PPM frames, e.g. to pipe them into an
+/// external encoder reading `image2pipe` input from stdin.
+pub struct PpmStream<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> PpmStream<W> {
+    #[inline]
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    #[inline]
+    pub fn write_frame(&mut self, canvas: &Canvas) -> Result<(), Error> {
+        canvas.write_ppm(&mut self.writer)?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> RenderTarget for PpmStream<W> {
+    #[inline]
+    fn present(&mut self, canvas: &Canvas) -> Result<(), Error> {
+        self.write_frame(canvas)
+    }
+}
+
+#[inline]
+fn luma(pixel: u32) -> u8 {
+    let [r, g, b] = to_rgb(pixel).map(u32::from);
+    ((299 * r + 587 * g + 114 * b + 500) / 1000) as u8
+}
+
+fn write_samples<const N: usize>(
+    writer: &mut impl Write,
+    canvas: &Canvas,
+    samples: impl Fn(u32) -> [u8; N],
+) -> Result<(), Error> {
+    let mut row = Vec::with_capacity(canvas.size().width as usize * N);
+    for pixels_row in canvas.pixels().rows() {
+        row.clear();
+        row.extend(pixels_row.iter().copied().flat_map(&samples));
+        writer.write_all(&row)?;
+    }
+    Ok(())
+}
+
+struct Header {
+    size: Size,
+    depth: usize,
+    maxval: u32,
+}
+
+impl Header {
+    fn read(reader: &mut impl BufRead) -> Result<Self, Error> {
+        let mut magic = [0; 2];
+        reader.read_exact(&mut magic)?;
+        let header = match &magic {
+            b"P5" | b"P6" => {
+                let width = read_number(reader)?;
+                let height = read_number(reader)?;
+                let maxval = read_number(reader)?;
+                // exactly one whitespace separates the header from the raster
+                if !read_byte(reader)?.is_ascii_whitespace() {
+                    return Err(Error::InvalidNetpbm("missing whitespace after header"));
+                }
+                Self {
+                    size: Size::new(width, height),
+                    depth: if &magic == b"P5" { 1 } else { 3 },
+                    maxval,
+                }
+            }
+            b"P7" => Self::read_pam(reader)?,
+            _ => return Err(Error::InvalidNetpbm("unsupported magic number")),
+        };
+        if !(1..=0xFFFF).contains(&header.maxval) {
+            return Err(Error::InvalidNetpbm("maxval is out of range"));
+        }
+        // a row buffer is allocated even for images without rows
+        let Size { width, height } = header.size;
+        if width > i32::MAX as u32
+            || height > i32::MAX as u32
+            || u64::from(width) * u64::from(height.max(1)) > PIXELS_MAX
+        {
+            return Err(Error::InvalidNetpbm("image is too large"));
+        }
+        Ok(header)
+    }
+
+    fn read_pam(reader: &mut impl BufRead) -> Result<Self, Error> {
+        let (mut width, mut height, mut depth, mut maxval) = (None, None, None, None);
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                return Err(Error::InvalidNetpbm("unterminated PAM header"));
+            }
+            let mut tokens = line.split_ascii_whitespace();
+            let (Some(key), value) = (tokens.next(), tokens.next()) else {
+                continue;
+            };
+            let parse = || -> Result<Option<u32>, Error> {
+                value
+                    .and_then(|value| value.parse().ok())
+                    .map(Some)
+                    .ok_or(Error::InvalidNetpbm("invalid PAM header value"))
+            };
+            match key {
+                "ENDHDR" => break,
+                "WIDTH" => width = parse()?,
+                "HEIGHT" => height = parse()?,
+                "DEPTH" => depth = parse()?,
+                "MAXVAL" => maxval = parse()?,
+                _ => {}
+            }
+        }
+        let (Some(width), Some(height), Some(depth), Some(maxval)) = (width, height, depth, maxval)
+        else {
+            return Err(Error::InvalidNetpbm("incomplete PAM header"));
+        };
+        if !(1..=4).contains(&depth) {
+            return Err(Error::InvalidNetpbm("unsupported PAM depth"));
+        }
+        Ok(Self {
+            size: Size::new(width, height),
+            depth: depth as usize,
+            maxval,
+        })
+    }
+}
+
+#[inline]
+fn read_byte(reader: &mut impl BufRead) -> Result<u8, Error> {
+    let mut byte = [0];
+    reader.read_exact(&mut byte)?;
+    Ok(byte[0])
+}
+
+/// Reads a decimal header field, skipping leading whitespace and `#` comments. The single
+/// whitespace byte terminating the number is left in the reader.
+fn read_number(reader: &mut impl BufRead) -> Result<u32, Error> {
+    let mut byte = read_byte(reader)?;
+    loop {
+        if byte == b'#' {
+            while read_byte(reader)? != b'\n' {}
+        } else if !byte.is_ascii_whitespace() {
+            break;
+        }
+        byte = read_byte(reader)?;
+    }
+    let mut value: u32 = 0;
+    loop {
+        if !byte.is_ascii_digit() {
+            return Err(Error::InvalidNetpbm("invalid header number"));
+        }
+        value = value
+            .checked_mul(10)
+            .and_then(|value| value.checked_add(u32::from(byte - b'0')))
+            .ok_or(Error::InvalidNetpbm("header number overflow"))?;
+        match reader.fill_buf()?.first() {
+            Some(next) if next.is_ascii_digit() => byte = read_byte(reader)?,
+            _ => return Ok(value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Pos;
+    use ndarray::array;
+
+    fn canvas() -> Canvas {
+        Canvas::from_pixels(array![
+            [0x000000, 0x123456, 0xFFFFFF],
+            [0xFF0000, 0x00FF00, 0x0000FF]
+        ])
+    }
+
+    #[test]
+    fn write_ppm() {
+        let mut data = vec![];
+        Canvas::from_pixels(array![[0x123456, 0xABCDEF]])
+            .write_ppm(&mut data)
+            .unwrap();
+        assert_eq!(data, b"P6\n2 1\n255\n\x12\x34\x56\xAB\xCD\xEF".as_slice());
+    }
+
+    #[test]
+    fn write_pgm() {
+        let mut data = vec![];
+        Canvas::from_pixels(array![[0x000000, 0xFFFFFF, 0x808080]])
+            .write_pgm(&mut data)
+            .unwrap();
+        assert_eq!(data, b"P5\n3 1\n255\n\x00\xFF\x80".as_slice());
+    }
+
+    #[test]
+    fn ppm_round_trip() {
+        let mut data = vec![];
+        canvas().write_ppm(&mut data).unwrap();
+        assert_eq!(
+            Canvas::read_netpbm(data.as_slice()).unwrap().pixels(),
+            canvas().pixels()
+        );
+    }
+
+    #[test]
+    fn pam_round_trip() {
+        let mut data = vec![];
+        canvas().write_pam(&mut data).unwrap();
+        assert_eq!(
+            Canvas::read_netpbm(data.as_slice()).unwrap().pixels(),
+            canvas().pixels()
+        );
+    }
+
+    #[test]
+    fn pgm_round_trip() {
+        let gray = Canvas::from_pixels(array![[0x000000, 0x7F7F7F], [0x010101, 0xFFFFFF]]);
+        let mut data = vec![];
+        gray.write_pgm(&mut data).unwrap();
+        assert_eq!(
+            Canvas::read_netpbm(data.as_slice()).unwrap().pixels(),
+            gray.pixels()
+        );
+    }
+
+    #[test]
+    fn read_with_comments_and_16_bit_samples() {
+        let data = b"P5 # comment\n1\t# another\n 2 65535\n\xFF\xFF\x80\x80";
+        assert_eq!(
+            Canvas::read_netpbm(data.as_slice()).unwrap().pixels(),
+            array![[0xFFFFFF], [0x808080]]
+        );
+    }
+
+    #[test]
+    fn read_pam_gray_alpha() {
+        let data = b"P7\nWIDTH 1\nHEIGHT 1\nDEPTH 2\nMAXVAL 255\nTUPLTYPE GRAYSCALE_ALPHA\nENDHDR\n\x40\x00";
+        assert_eq!(
+            Canvas::read_netpbm(data.as_slice()).unwrap().pixels(),
+            array![[0x404040]]
+        );
+    }
+
+    #[test]
+    fn stream_round_trip() {
+        let mut stream = PpmStream::new(vec![]);
+        let mut frame = canvas();
+        stream.write_frame(&frame).unwrap();
+        frame.draw_dot(Pos::new(0, 0), 0x010203);
+        stream.write_frame(&frame).unwrap();
+        let data = stream.into_inner();
+
+        let mut reader = data.as_slice();
+        assert_eq!(
+            Canvas::read_netpbm(&mut reader).unwrap().pixels(),
+            canvas().pixels()
+        );
+        assert_eq!(
+            Canvas::read_netpbm(&mut reader).unwrap().pixels(),
+            frame.pixels()
+        );
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn read_invalid_magic() {
+        assert!(matches!(
+            Canvas::read_netpbm(b"P3\n1 1\n255\n0 0 0\n".as_slice()),
+            Err(Error::InvalidNetpbm("unsupported magic number"))
+        ));
+    }
+
+    #[test]
+    fn read_sample_exceeds_maxval() {
+        assert!(matches!(
+            Canvas::read_netpbm(b"P5\n1 1\n15\n\x10".as_slice()),
+            Err(Error::InvalidNetpbm("sample exceeds maxval"))
+        ));
+    }
+
+    #[test]
+    fn read_too_large() {
+        for header in [
+            b"P6\n40000 40000\n255\n".as_slice(),
+            b"P5\n2147483647 0\n255\n",
+            b"P7\nWIDTH 65536\nHEIGHT 65536\nDEPTH 4\nMAXVAL 255\nENDHDR\n",
+        ] {
+            assert!(matches!(
+                Canvas::read_netpbm(header),
+                Err(Error::InvalidNetpbm("image is too large"))
+            ));
+        }
+    }
+
+    #[test]
+    fn read_truncated() {
+        assert!(matches!(
+            Canvas::read_netpbm(b"P6\n2 2\n255\n\x00\x00".as_slice()),
+            Err(Error::Io(_))
+        ));
+    }
+}
@@ -0,0 +1,130 @@
+use crate::image::{from_rgb, to_rgb};
+use crate::{Canvas, Error};
+use ndarray::Array2;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+impl Canvas {
+    /// Encodes the canvas as an 8-bit RGB PNG into `writer`.
+    pub fn write_png(&self, writer: impl Write) -> Result<(), Error> {
+        let size = self.size();
+        let mut encoder = png::Encoder::new(writer, size.width, size.height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let data: Vec<u8> = self.pixels().iter().copied().flat_map(to_rgb).collect();
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&data)?;
+        writer.finish()?;
+        Ok(())
+    }
+
+    #[inline]
+    pub fn save_png(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_png(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Decodes a PNG image of any color type and bit depth. Alpha is discarded and 16-bit
+    /// samples are truncated to 8 bits.
+    pub fn read_png(reader: impl Read) -> Result<Self, Error> {
+        let mut decoder = png::Decoder::new(reader);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info()?;
+        if reader.info().width > i32::MAX as u32 || reader.info().height > i32::MAX as u32 {
+            return Err(png::DecodingError::LimitsExceeded.into());
+        }
+        let mut data = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut data)?;
+        let channels = info.color_type.samples();
+        let pixels =
+            Array2::from_shape_fn((info.height as usize, info.width as usize), |(y, x)| {
+                let offset = y * info.line_size + x * channels;
+                match info.color_type {
+                    png::ColorType::Grayscale | png::ColorType::GrayscaleAlpha => {
+                        from_rgb([data[offset]; 3])
+                    }
+                    _ => from_rgb([data[offset], data[offset + 1], data[offset + 2]]),
+                }
+            });
+        Ok(Canvas::from_pixels(pixels))
+    }
+
+    #[inline]
+    pub fn open_png(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::read_png(BufReader::new(File::open(path)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Pos, Size};
+
+    #[test]
+    fn write_png() {
+        let mut canvas = Canvas::new(0x123456, Size::new(3, 2));
+        canvas.draw_dot(Pos::new(2, 1), 0xFFAB_CDEF);
+        let mut data = vec![];
+        canvas.write_png(&mut data).unwrap();
+
+        let mut reader = png::Decoder::new(data.as_slice()).read_info().unwrap();
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf).unwrap();
+        assert_eq!((info.width, info.height), (3, 2));
+        assert_eq!(info.color_type, png::ColorType::Rgb);
+        assert_eq!(
+            &buf[..info.buffer_size()],
+            [
+                [0x12, 0x34, 0x56],
+                [0x12, 0x34, 0x56],
+                [0x12, 0x34, 0x56],
+                [0x12, 0x34, 0x56],
+                [0x12, 0x34, 0x56],
+                [0xAB, 0xCD, 0xEF]
+            ]
+            .concat()
+        );
+    }
+
+    #[test]
+    fn png_round_trip() {
+        let canvas = Canvas::from_pixels(ndarray::array![
+            [0x000000, 0x123456, 0xFFFFFF],
+            [0xFF0000, 0x00FF00, 0x0000FF]
+        ]);
+        let mut data = vec![];
+        canvas.write_png(&mut data).unwrap();
+        assert_eq!(
+            Canvas::read_png(data.as_slice()).unwrap().pixels(),
+            canvas.pixels()
+        );
+    }
+
+    #[test]
+    fn read_gray_alpha_16_bit() {
+        let mut data = vec![];
+        let mut encoder = png::Encoder::new(&mut data, 2, 1);
+        encoder.set_color(png::ColorType::GrayscaleAlpha);
+        encoder.set_depth(png::BitDepth::Sixteen);
+        let mut writer = encoder.write_header().unwrap();
+        writer
+            .write_image_data(&[0x12, 0x34, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF])
+            .unwrap();
+        writer.finish().unwrap();
+        assert_eq!(
+            Canvas::read_png(data.as_slice()).unwrap().pixels(),
+            ndarray::array![[0x121212, 0xFFFFFF]]
+        );
+    }
+
+    #[test]
+    fn read_invalid_png() {
+        assert!(matches!(
+            Canvas::read_png(b"\x89PNG\r\n\x1a\n".as_slice()),
+            Err(Error::PngDecoding(_))
+        ));
+    }
+}
@@ -0,0 +1,306 @@
+use crate::image::{from_rgb, to_rgb};
+use crate::{Canvas, Error};
+use ndarray::Array2;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+const MAGIC: &[u8; 4] = b"qoif";
+const END_MARKER: [u8; 8] = [0, 0, 0, 0, 0, 0, 0, 1];
+/// Same guard as the reference decoder against absurd header dimensions.
+const PIXELS_MAX: u64 = 400_000_000;
+
+const OP_INDEX: u8 = 0x00;
+const OP_DIFF: u8 = 0x40;
+const OP_LUMA: u8 = 0x80;
+const OP_RUN: u8 = 0xC0;
+const OP_RGB: u8 = 0xFE;
+const OP_RGBA: u8 = 0xFF;
+const MASK_2: u8 = 0xC0;
+const RUN_MAX: u8 = 62;
+
+#[derive(Eq, PartialEq, Debug, Copy, Clone)]
+struct Rgba([u8; 4]);
+
+impl Rgba {
+    const START: Self = Self([0, 0, 0, 0xFF]);
+
+    #[inline]
+    fn from_pixel(pixel: u32) -> Self {
+        let [r, g, b] = to_rgb(pixel);
+        Self([r, g, b, 0xFF])
+    }
+
+    #[inline]
+    fn to_pixel(self) -> u32 {
+        let [r, g, b, _] = self.0;
+        from_rgb([r, g, b])
+    }
+
+    #[inline]
+    fn hash(self) -> usize {
+        let [r, g, b, a] = self.0.map(usize::from);
+        (r * 3 + g * 5 + b * 7 + a * 11) % 64
+    }
+}
+
+impl Canvas {
+    /// Encodes the canvas as an RGB QOI image.
+    pub fn write_qoi(&self, mut writer: impl Write) -> Result<(), Error> {
+        let size = self.size();
+        let mut data = Vec::with_capacity(14 + self.pixels().len() + END_MARKER.len());
+        data.extend_from_slice(MAGIC);
+        data.extend_from_slice(&size.width.to_be_bytes());
+        data.extend_from_slice(&size.height.to_be_bytes());
+        // 3 channels, sRGB with linear alpha
+        data.extend_from_slice(&[3, 0]);
+
+        let mut index = [Rgba([0; 4]); 64];
+        let mut prev = Rgba::START;
+        let mut run = 0;
+        for px in self.pixels().iter().copied().map(Rgba::from_pixel) {
+            if px == prev {
+                run += 1;
+                if run == RUN_MAX {
+                    data.push(OP_RUN | (run - 1));
+                    run = 0;
+                }
+                continue;
+            }
+            if run > 0 {
+                data.push(OP_RUN | (run - 1));
+                run = 0;
+            }
+
+            let hash = px.hash();
+            if index[hash] == px {
+                data.push(OP_INDEX | hash as u8);
+            } else {
+                index[hash] = px;
+                let [r, g, b, _] = px.0;
+                let [pr, pg, pb, _] = prev.0;
+                let vr = r.wrapping_sub(pr) as i8;
+                let vg = g.wrapping_sub(pg) as i8;
+                let vb = b.wrapping_sub(pb) as i8;
+                let vg_r = vr.wrapping_sub(vg);
+                let vg_b = vb.wrapping_sub(vg);
+                if [vr, vg, vb].iter().all(|v| (-2..=1).contains(v)) {
+                    data.push(
+                        OP_DIFF | ((vr + 2) as u8) << 4 | ((vg + 2) as u8) << 2 | (vb + 2) as u8,
+                    );
+                } else if (-32..=31).contains(&vg)
+                    && (-8..=7).contains(&vg_r)
+                    && (-8..=7).contains(&vg_b)
+                {
+                    data.push(OP_LUMA | (vg + 32) as u8);
+                    data.push(((vg_r + 8) as u8) << 4 | (vg_b + 8) as u8);
+                } else {
+                    data.extend_from_slice(&[OP_RGB, r, g, b]);
+                }
+            }
+            prev = px;
+        }
+        if run > 0 {
+            data.push(OP_RUN | (run - 1));
+        }
+        data.extend_from_slice(&END_MARKER);
+        writer.write_all(&data)?;
+        Ok(())
+    }
+
+    #[inline]
+    pub fn save_qoi(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_qoi(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Decodes one QOI image from `reader`, dropping the alpha channel if present. Nothing past
+    /// the end marker is consumed.
+    pub fn read_qoi(mut reader: impl BufRead) -> Result<Self, Error> {
+        let mut header = [0; 14];
+        reader.read_exact(&mut header)?;
+        if &header[..4] != MAGIC {
+            return Err(Error::InvalidQoi("invalid magic"));
+        }
+        let [width, height] = [&header[4..8], &header[8..12]]
+            .map(|bytes| u32::from_be_bytes(bytes.try_into().unwrap_or_default()));
+        if !matches!(header[12], 3 | 4) || header[13] > 1 {
+            return Err(Error::InvalidQoi("invalid channels or colorspace"));
+        }
+        if width > i32::MAX as u32
+            || height > i32::MAX as u32
+            || u64::from(width) * u64::from(height) > PIXELS_MAX
+        {
+            return Err(Error::InvalidQoi("image is too large"));
+        }
+
+        let mut read_byte = || -> Result<u8, Error> {
+            let mut byte = [0];
+            reader.read_exact(&mut byte)?;
+            Ok(byte[0])
+        };
+        let mut pixels = Array2::zeros((height as usize, width as usize));
+        let mut index = [Rgba([0; 4]); 64];
+        let mut px = Rgba::START;
+        let mut run = 0;
+        for pixel in pixels.iter_mut() {
+            if run > 0 {
+                run -= 1;
+            } else {
+                let op = read_byte()?;
+                match op {
+                    OP_RGB => {
+                        for channel in &mut px.0[..3] {
+                            *channel = read_byte()?;
+                        }
+                    }
+                    OP_RGBA => {
+                        for channel in &mut px.0 {
+                            *channel = read_byte()?;
+                        }
+                    }
+                    _ => match op & MASK_2 {
+                        OP_INDEX => px = index[usize::from(op)],
+                        OP_DIFF => {
+                            let [r, g, b, _] = &mut px.0;
+                            *r = r.wrapping_add((op >> 4) & 0x03).wrapping_sub(2);
+                            *g = g.wrapping_add((op >> 2) & 0x03).wrapping_sub(2);
+                            *b = b.wrapping_add(op & 0x03).wrapping_sub(2);
+                        }
+                        OP_LUMA => {
+                            let next = read_byte()?;
+                            let vg = (op & 0x3F).wrapping_sub(32);
+                            let [r, g, b, _] = &mut px.0;
+                            *r = r.wrapping_add(vg.wrapping_sub(8).wrapping_add(next >> 4));
+                            *g = g.wrapping_add(vg);
+                            *b = b.wrapping_add(vg.wrapping_sub(8).wrapping_add(next & 0x0F));
+                        }
+                        _ => run = op & 0x3F,
+                    },
+                }
+                index[px.hash()] = px;
+            }
+            *pixel = px.to_pixel();
+        }
+
+        let mut end_marker = [0; END_MARKER.len()];
+        reader.read_exact(&mut end_marker)?;
+        if end_marker != END_MARKER {
+            return Err(Error::InvalidQoi("missing end marker"));
+        }
+        Ok(Canvas::from_pixels(pixels))
+    }
+
+    #[inline]
+    pub fn open_qoi(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::read_qoi(BufReader::new(File::open(path)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+
+    fn encode(canvas: &Canvas) -> Vec<u8> {
+        let mut data = vec![];
+        canvas.write_qoi(&mut data).unwrap();
+        data
+    }
+
+    fn body(data: &[u8]) -> &[u8] {
+        &data[14..data.len() - END_MARKER.len()]
+    }
+
+    #[test]
+    fn header() {
+        let data = encode(&Canvas::from_pixels(array![[0x000000, 0x000000]]));
+        assert_eq!(&data[..14], b"qoif\0\0\0\x02\0\0\0\x01\x03\x00");
+        assert_eq!(&data[data.len() - 8..], END_MARKER);
+    }
+
+    #[test]
+    fn run() {
+        let data = encode(&Canvas::from_pixels(Array2::zeros((1, 64))));
+        assert_eq!(body(&data), [OP_RUN | 61, OP_RUN | 1]);
+    }
+
+    #[test]
+    fn diff_luma_rgb_index() {
+        let data = encode(&Canvas::from_pixels(array![[
+            0x01FF00, 0x0A0507, 0x808080, 0x01FF00
+        ]]));
+        assert_eq!(
+            body(&data),
+            [
+                OP_DIFF | 3 << 4 | 1 << 2 | 2,
+                OP_LUMA | (6 + 32),
+                (3 + 8) << 4 | (1 + 8),
+                OP_RGB,
+                0x80,
+                0x80,
+                0x80,
+                OP_INDEX | Rgba::from_pixel(0x01FF00).hash() as u8,
+            ]
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let canvas = Canvas::from_pixels(array![
+            [0x000000, 0x000000, 0x010203, 0xFFFFFF, 0xFFFFFE],
+            [0x123456, 0x000000, 0x123456, 0x808080, 0x7F8081],
+            [0xFF0000, 0x00FF00, 0x0000FF, 0xFF0000, 0xFF0000]
+        ]);
+        assert_eq!(
+            Canvas::read_qoi(encode(&canvas).as_slice())
+                .unwrap()
+                .pixels(),
+            canvas.pixels()
+        );
+    }
+
+    #[test]
+    fn round_trip_gradient() {
+        let canvas = Canvas::from_pixels(Array2::from_shape_fn((16, 200), |(y, x)| {
+            (x as u32 * 0x010305) ^ (y as u32 * 0x110000)
+        }));
+        assert_eq!(
+            Canvas::read_qoi(encode(&canvas).as_slice())
+                .unwrap()
+                .pixels(),
+            canvas.pixels()
+        );
+    }
+
+    #[test]
+    fn read_rgba() {
+        let mut data = b"qoif\0\0\0\x02\0\0\0\x01\x04\x00".to_vec();
+        data.extend_from_slice(&[OP_RGBA, 1, 2, 3, 4, OP_RUN]);
+        data.extend_from_slice(&END_MARKER);
+        assert_eq!(
+            Canvas::read_qoi(data.as_slice()).unwrap().pixels(),
+            array![[0x010203, 0x010203]]
+        );
+    }
+
+    #[test]
+    fn read_invalid_magic() {
+        assert!(matches!(
+            Canvas::read_qoi(b"qoiF\0\0\0\x01\0\0\0\x01\x03\x00".as_slice()),
+            Err(Error::InvalidQoi("invalid magic"))
+        ));
+    }
+
+    #[test]
+    fn read_missing_end_marker() {
+        let mut data = encode(&Canvas::from_pixels(array![[0x123456]]));
+        *data.last_mut().unwrap() = 0;
+        assert!(matches!(
+            Canvas::read_qoi(data.as_slice()),
+            Err(Error::InvalidQoi("missing end marker"))
+        ));
+    }
+}
@@ -0,0 +1,242 @@
+use crate::image::to_rgb;
+use crate::target::RenderTarget;
+use crate::{Canvas, Error, Size};
+use std::io::Write;
+
+/// Chroma layout of the planes written by [`Y4mWriter`].
+#[derive(Eq, PartialEq, Debug, Copy, Clone, Default)]
+pub enum Chroma {
+    /// Full resolution chroma, lossless apart from the color conversion.
+    C444,
+    /// Chroma averaged over 2x2 blocks, the layout most encoders expect.
+    #[default]
+    C420,
+}
+
+impl Chroma {
+    #[inline]
+    fn tag(self) -> &'static str {
+        match self {
+            Chroma::C444 => "444",
+            Chroma::C420 => "420jpeg",
+        }
+    }
+}
+
+/// Writes canvases as frames of a YUV4MPEG2 stream with BT.601 limited range colors.
+///
+/// The stream header is written with the first frame, which also fixes the frame size.
+pub struct Y4mWriter<W: Write> {
+    writer: W,
+    fps: u32,
+    chroma: Chroma,
+    size: Option<Size>,
+    frame: Vec<u8>,
+}
+
+impl<W: Write> Y4mWriter<W> {
+    #[inline]
+    pub fn new(writer: W, fps: u32, chroma: Chroma) -> Self {
+        Self {
+            writer,
+            fps,
+            chroma,
+            size: None,
+            frame: vec![],
+        }
+    }
+
+    #[inline]
+    pub fn size(&self) -> Option<Size> {
+        self.size
+    }
+
+    pub fn write_frame(&mut self, canvas: &Canvas) -> Result<(), Error> {
+        let size = canvas.size();
+        match self.size {
+            None => {
+                writeln!(
+                    self.writer,
+                    "YUV4MPEG2 W{} H{} F{}:1 Ip A1:1 C{}",
+                    size.width,
+                    size.height,
+                    self.fps,
+                    self.chroma.tag()
+                )?;
+                self.size = Some(size);
+            }
+            Some(expected) if expected != size => {
+                return Err(Error::FrameSizeMismatch {
+                    expected,
+                    actual: size,
+                })
+            }
+            Some(_) => {}
+        }
+
+        self.frame.clear();
+        self.frame.extend_from_slice(b"FRAME\n");
+        let pixels = canvas.pixels();
+        self.frame
+            .extend(pixels.iter().map(|&pixel| luma(to_rgb(pixel))));
+        match self.chroma {
+            Chroma::C444 => {
+                for plane in [u_chroma, v_chroma] {
+                    self.frame
+                        .extend(pixels.iter().map(|&pixel| plane(to_rgb(pixel))));
+                }
+            }
+            Chroma::C420 => {
+                let (height, width) = pixels.dim();
+                for plane in [u_chroma, v_chroma] {
+                    for y in (0..height).step_by(2) {
+                        for x in (0..width).step_by(2) {
+                            // odd edges replicate the last row or column
+                            let block =
+                                [(y, x), (y, x + 1), (y + 1, x), (y + 1, x + 1)].map(|(y, x)| {
+                                    to_rgb(pixels[(y.min(height - 1), x.min(width - 1))])
+                                });
+                            let mut sum = [0; 3];
+                            for rgb in block {
+                                for (sum, channel) in sum.iter_mut().zip(rgb) {
+                                    *sum += u32::from(channel);
+                                }
+                            }
+                            self.frame.push(plane(sum.map(|sum| ((sum + 2) / 4) as u8)));
+                        }
+                    }
+                }
+            }
+        }
+        self.writer.write_all(&self.frame)?;
+        Ok(())
+    }
+
+    #[inline]
+    pub fn flush(&mut self) -> Result<(), Error> {
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> RenderTarget for Y4mWriter<W> {
+    #[inline]
+    fn present(&mut self, canvas: &Canvas) -> Result<(), Error> {
+        self.write_frame(canvas)
+    }
+
+    #[inline]
+    fn close(&mut self) -> Result<(), Error> {
+        self.flush()
+    }
+}
+
+#[inline]
+fn luma(rgb: [u8; 3]) -> u8 {
+    let [r, g, b] = rgb.map(i32::from);
+    (((66 * r + 129 * g + 25 * b + 128) >> 8) + 16) as u8
+}
+
+#[inline]
+fn u_chroma(rgb: [u8; 3]) -> u8 {
+    let [r, g, b] = rgb.map(i32::from);
+    (((-38 * r - 74 * g + 112 * b + 128) >> 8) + 128) as u8
+}
+
+#[inline]
+fn v_chroma(rgb: [u8; 3]) -> u8 {
+    let [r, g, b] = rgb.map(i32::from);
+    (((112 * r - 94 * g - 18 * b + 128) >> 8) + 128) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+
+    #[test]
+    fn bt601_limited_range() {
+        assert_eq!(
+            [luma([0, 0, 0]), u_chroma([0, 0, 0]), v_chroma([0, 0, 0])],
+            [16, 128, 128]
+        );
+        assert_eq!(
+            [
+                luma([255, 255, 255]),
+                u_chroma([255, 255, 255]),
+                v_chroma([255, 255, 255])
+            ],
+            [235, 128, 128]
+        );
+        assert_eq!(
+            [
+                luma([255, 0, 0]),
+                u_chroma([255, 0, 0]),
+                v_chroma([255, 0, 0])
+            ],
+            [82, 90, 240]
+        );
+    }
+
+    #[test]
+    fn write_444() {
+        let mut writer = Y4mWriter::new(vec![], 30, Chroma::C444);
+        writer
+            .write_frame(&Canvas::from_pixels(array![[0x000000, 0xFFFFFF]]))
+            .unwrap();
+        assert_eq!(
+            writer.into_inner(),
+            b"YUV4MPEG2 W2 H1 F30:1 Ip A1:1 C444\nFRAME\n\x10\xEB\x80\x80\x80\x80".as_slice()
+        );
+    }
+
+    #[test]
+    fn write_420_odd_size() {
+        let mut writer = Y4mWriter::new(vec![], 60, Chroma::C420);
+        let frame = Canvas::from_pixels(array![
+            [0xFF0000, 0xFF0000, 0x000000],
+            [0xFF0000, 0xFF0000, 0x000000],
+            [0x000000, 0x000000, 0x000000]
+        ]);
+        writer.write_frame(&frame).unwrap();
+        writer.write_frame(&frame).unwrap();
+        let header = b"YUV4MPEG2 W3 H3 F60:1 Ip A1:1 C420jpeg\n";
+        let frame = [
+            b"FRAME\n".as_slice(),
+            &[82, 82, 16, 82, 82, 16, 16, 16, 16],
+            &[90, 128, 128, 128],
+            &[240, 128, 128, 128],
+        ]
+        .concat();
+        assert_eq!(
+            writer.into_inner(),
+            [header.as_slice(), &frame, &frame].concat()
+        );
+    }
+
+    #[test]
+    fn frame_size_mismatch() {
+        let mut writer = Y4mWriter::new(vec![], 60, Chroma::C420);
+        writer
+            .write_frame(&Canvas::from_pixels(array![[0]]))
+            .unwrap();
+        assert!(matches!(
+            writer.write_frame(&Canvas::from_pixels(array![[0, 0]])),
+            Err(Error::FrameSizeMismatch {
+                expected: Size {
+                    width: 1,
+                    height: 1
+                },
+                actual: Size {
+                    width: 2,
+                    height: 1
+                }
+            })
+        ));
+    }
+}
@@ -1,10 +1,25 @@
 pub mod canvas;
 pub mod colors;
+pub mod draw;
+mod error;
+pub mod filter;
+pub mod font;
+pub mod image;
 pub mod pos;
 pub mod rect;
+pub mod region;
+pub mod scene;
 pub mod size;
+pub mod svg;
+pub mod target;
+pub mod terminal;
 
 pub use canvas::Canvas;
+pub use draw::Draw;
+pub use error::Error;
+pub use filter::Filter;
+pub use font::Font;
 pub use pos::Pos;
 pub use rect::Rect;
+pub use region::Region;
 pub use size::Size;
@@ -1,73 +1,373 @@
-use std::num::NonZeroU32;
+mod overlay;
+mod paced;
+
+use anyhow::{bail, Context};
+use glam::Affine2;
+use overlay::{CountingDraw, Overlay};
+use paced::Paced;
+use std::fs::File;
+use std::io::{stdout, BufWriter};
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
-use verle::colors::{GRAY, GREEN, RED};
-use verle::Canvas;
-use verle::Pos;
-use verle::Size;
+use std::thread::sleep;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use verle::canvas::{TileOptions, TiledRenderer};
+use verle::colors::GRAY;
+use verle::image::{Chroma, GifOptions, GifWriter, PpmStream, Y4mWriter};
+use verle::svg::SvgRecorder;
+use verle::target::{FileTarget, RenderTarget, SoftbufferTarget};
+use verle::terminal::TerminalPresenter;
+use verle::{scene, Canvas, Filter, Size};
 use winit::dpi::PhysicalSize;
-use winit::event::{Event, WindowEvent};
+use winit::event::{ElementState, Event, KeyEvent, WindowEvent};
 use winit::event_loop::EventLoop;
+use winit::keyboard::{KeyCode, PhysicalKey};
 use winit::window::{Icon, WindowBuilder};
 
-fn main() {
-    let event_loop = EventLoop::new().unwrap();
+/// Frame rate of Y4M recordings made from the window. The window presents as fast as it can, so
+/// frames are repeated or dropped to keep the recording at this rate.
+const WINDOW_RECORDING_FPS: u32 = 60;
+
+const USAGE: &str = "\
+Usage: verle [--headless [OPTIONS]]
+
+In the window press R to start or stop recording a Y4M video into the current directory
+and F3 to show or hide the debug overlay.
+
+Options:
+    --headless          Render without a window
+    --frames <N>        Number of frames to render headless [default: 1]
+    --size <WxH>        Canvas size for headless rendering [default: 640x480]
+    --output <PATH>     Write the final frame to PATH, format is taken from the extension;
+                        a {} in PATH writes every frame, numbered, instead
+    --ppm-stream        Write every frame as binary PPM to stdout
+    --record <PATH>     Record every frame into a Y4M video at PATH
+    --gif <PATH>        Record every frame into an animated GIF at PATH
+    --svg <PATH>        Write the final frame as SVG vector graphics to PATH
+    --terminal          Show every frame on this ANSI truecolor terminal, paced at --fps;
+                        the size is taken from $COLUMNS and $LINES [default: 80x24]
+    --fps <N>           Frame rate of the recorded video or GIF, GIFs play at most
+                        50 frames per second [default: 60]
+    --overlay           Draw the debug overlay into every frame
+    -h, --help          Print this help";
+
+struct Headless {
+    frames: u32,
+    size: Size,
+    output: Option<PathBuf>,
+    ppm_stream: bool,
+    record: Option<PathBuf>,
+    gif: Option<PathBuf>,
+    svg: Option<PathBuf>,
+    terminal: bool,
+    fps: u32,
+    overlay: bool,
+}
+
+impl Default for Headless {
+    fn default() -> Self {
+        Self {
+            frames: 1,
+            size: Size::new(640, 480),
+            output: None,
+            ppm_stream: false,
+            record: None,
+            gif: None,
+            svg: None,
+            terminal: false,
+            fps: 60,
+            overlay: false,
+        }
+    }
+}
+
+enum Mode {
+    Window,
+    Headless(Headless),
+    Help,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> anyhow::Result<Mode> {
+    let mut headless = None;
+    let mut options = Headless::default();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .with_context(|| format!("{arg} requires a value"))
+        };
+        match arg.as_str() {
+            "--headless" => headless = Some(()),
+            "--frames" => options.frames = value()?.parse().context("invalid --frames")?,
+            "--size" => {
+                let value = value()?;
+                let (width, height) = value
+                    .split_once('x')
+                    .with_context(|| format!("invalid --size {value}, expected WxH"))?;
+                options.size = Size::new(
+                    width.parse().context("invalid --size width")?,
+                    height.parse().context("invalid --size height")?,
+                );
+            }
+            "--output" => options.output = Some(value()?.into()),
+            "--ppm-stream" => options.ppm_stream = true,
+            "--record" => options.record = Some(value()?.into()),
+            "--gif" => options.gif = Some(value()?.into()),
+            "--svg" => options.svg = Some(value()?.into()),
+            "--terminal" => options.terminal = true,
+            "--fps" => {
+                options.fps = value()?.parse().context("invalid --fps")?;
+                if options.fps == 0 {
+                    bail!("invalid --fps 0, expected at least 1");
+                }
+            }
+            "--overlay" => options.overlay = true,
+            "-h" | "--help" => return Ok(Mode::Help),
+            _ => bail!("unexpected argument {arg}\n\n{USAGE}"),
+        }
+    }
+    Ok(match headless {
+        Some(()) => Mode::Headless(options),
+        None => Mode::Window,
+    })
+}
+
+fn main() -> anyhow::Result<()> {
+    match parse_args(std::env::args().skip(1))? {
+        Mode::Window => run_window(),
+        Mode::Headless(options) => run_headless(&options),
+        Mode::Help => {
+            println!("{USAGE}");
+            Ok(())
+        }
+    }
+}
+
+fn run_headless(options: &Headless) -> anyhow::Result<()> {
+    if i32::try_from(options.size.width).is_err() || i32::try_from(options.size.height).is_err() {
+        bail!("{} is too large", options.size);
+    }
+    if options.ppm_stream && options.terminal {
+        bail!("--ppm-stream and --terminal both write to stdout");
+    }
+    let mut targets: Vec<Box<dyn RenderTarget>> = vec![];
+    if options.ppm_stream {
+        targets.push(Box::new(PpmStream::new(stdout().lock())));
+    }
+    if let Some(path) = &options.record {
+        targets.push(Box::new(create_recorder(path, options.fps)?));
+    }
+    if let Some(path) = &options.gif {
+        let file =
+            File::create(path).with_context(|| format!("failed to create {}", path.display()))?;
+        let options = GifOptions {
+            // rounded to hundredths, the writer raises it to 2 so frames above 50 fps play slower
+            delay: u16::try_from((100 + options.fps / 2) / options.fps).unwrap_or(u16::MAX),
+            only_changed: true,
+            ..Default::default()
+        };
+        targets.push(Box::new(GifWriter::new(BufWriter::new(file), options)));
+    }
+    if options.terminal {
+        targets.push(Box::new(TerminalPresenter::new(
+            stdout().lock(),
+            terminal_cells(),
+        )));
+    }
+    let numbered_output = options
+        .output
+        .as_deref()
+        .filter(|output| output.to_str().is_some_and(|output| output.contains("{}")));
+    if let Some(output) = numbered_output {
+        targets.push(Box::new(FileTarget::new(output)));
+    }
+
+    let frame_time = Duration::from_secs(1) / options.fps;
+    let mut canvas = Canvas::new(GRAY, options.size);
+    let mut overlay = Overlay::new(options.overlay);
+    let mut last_frame_start = None;
+    for _ in 0..options.frames {
+        let frame_start = Instant::now();
+        if let Some(last_frame_start) = last_frame_start.replace(frame_start) {
+            overlay.record_frame(frame_start - last_frame_start);
+        }
+        canvas.clear(GRAY);
+        let (step_time, counters) = draw_scene(&mut canvas);
+        overlay.draw(&mut canvas, step_time, &counters);
+        for target in &mut targets {
+            target.present(&canvas)?;
+        }
+        if options.terminal {
+            sleep(frame_time.saturating_sub(frame_start.elapsed()));
+        }
+    }
+    for target in &mut targets {
+        target.close()?;
+    }
+    if let (Some(output), None) = (&options.output, numbered_output) {
+        FileTarget::new(output)
+            .present(&canvas)
+            .with_context(|| format!("failed to write {}", output.display()))?;
+    }
+    if let Some(svg) = &options.svg {
+        let mut recorder = SvgRecorder::new(GRAY, options.size);
+        scene::draw(&mut recorder);
+        recorder
+            .save_svg(svg)
+            .with_context(|| format!("failed to write {}", svg.display()))?;
+    }
+    Ok(())
+}
+
+/// Draws the demo scene, returning how long it took and how many shapes it drew.
+fn draw_scene(canvas: &mut Canvas) -> (Duration, [(&'static str, u64); 3]) {
+    let start = Instant::now();
+    let mut renderer = TiledRenderer::new(canvas.size(), TileOptions::default());
+    let mut counting = CountingDraw::new(&mut renderer);
+    scene::draw(&mut counting);
+    let counters = counting.counters();
+    renderer.render(canvas);
+    (start.elapsed(), counters)
+}
+
+/// Terminal size in character cells, leaving the last line free so drawing never scrolls.
+fn terminal_cells() -> Size {
+    let var = |name: &str, default: u32| {
+        std::env::var(name)
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(default)
+    };
+    Size::new(
+        var("COLUMNS", 80),
+        var("LINES", 24).saturating_sub(1).max(1),
+    )
+}
+
+fn create_recorder(path: &Path, fps: u32) -> anyhow::Result<Y4mWriter<BufWriter<File>>> {
+    let file =
+        File::create(path).with_context(|| format!("failed to create {}", path.display()))?;
+    Ok(Y4mWriter::new(BufWriter::new(file), fps, Chroma::C420))
+}
+
+fn run_window() -> anyhow::Result<()> {
+    let event_loop = EventLoop::new()?;
     let window = Rc::new(
         WindowBuilder::new()
             .with_title("verle")
             .with_inner_size(PhysicalSize::new(640, 480))
             // .with_resizable(false)
             .with_window_icon(Icon::from_rgba(vec![0x00, 0xff, 0x00, 0xff], 1, 1).ok())
-            .build(&event_loop)
-            .unwrap(),
+            .build(&event_loop)?,
     );
-    let context = softbuffer::Context::new(window.clone()).unwrap();
-    let mut surface = softbuffer::Surface::new(&context, window.clone()).unwrap();
-    event_loop
-        .run(move |event, elwt| match event {
-            Event::WindowEvent {
-                event: WindowEvent::RedrawRequested,
-                window_id,
-            } if window_id == window.id() => {
-                let size = window.inner_size();
-                let (Some(width), Some(height)) =
-                    (NonZeroU32::new(size.width), NonZeroU32::new(size.height))
-                else {
-                    return;
-                };
-                surface.resize(width, height).unwrap();
-                let size = Size::new(u32::from(width), u32::from(height));
-
-                let mut canvas = Canvas::new(GRAY, size);
-
-                let (width, height) = (size.width as i32, size.height as i32);
-
-                canvas.draw_circle(Pos::new(0, 0), 1, RED, GREEN);
-                canvas.draw_circle(Pos::new(width - 1, 0), 1, RED, GREEN);
-                canvas.draw_circle(Pos::new(0, height - 1), 1, RED, GREEN);
-                canvas.draw_circle(Pos::new(width - 1, height - 1), 1, RED, GREEN);
-                canvas.draw_line(Pos::new(0, 0), Pos::new(width, height), RED);
-                canvas.draw_line(Pos::new(width + 10, height), Pos::new(10, 0), RED);
-                canvas.draw_line(Pos::new(width, 0), Pos::new(0, height), RED);
-                canvas.draw_line(Pos::new(10, height), Pos::new(width + 10, 0), RED);
-                canvas.draw_line(Pos::new(100, 100), Pos::new(100, 200), GREEN);
-                canvas.draw_line(Pos::new(100, 100), Pos::new(200, 100), GREEN);
-                canvas.draw_line(Pos::new(200, 200), Pos::new(100, 200), GREEN);
-                canvas.draw_line(Pos::new(200, 200), Pos::new(200, 100), GREEN);
-
-                let mut buffer = surface.buffer_mut().unwrap();
-                window.pre_present_notify();
-                canvas.flush(&mut buffer).unwrap();
-                buffer.present().unwrap();
-
-                window.request_redraw();
+    let mut target = SoftbufferTarget::new(window.clone(), window.clone())?;
+    let mut recorder: Option<Paced<Y4mWriter<_>>> = None;
+    let mut overlay = Overlay::new(false);
+    overlay.set_scene_cached(true);
+    let mut last_frame_start = None;
+    // the scene stays the same, so it is drawn once per size and only the overlay is redrawn
+    // over it; the overlay shows the scene statistics of that draw
+    let mut canvas = Canvas::new(GRAY, Size::default());
+    let mut scene_canvas = Canvas::new(GRAY, Size::default());
+    let mut scene_stats = Default::default();
+    let mut overlay_rect = None;
+    event_loop.run(move |event, elwt| match event {
+        Event::WindowEvent {
+            event: WindowEvent::RedrawRequested,
+            window_id,
+        } if window_id == window.id() => {
+            let size = window.inner_size();
+            let size = Size::new(size.width, size.height);
+
+            let frame_start = Instant::now();
+            if let Some(last_frame_start) = last_frame_start.replace(frame_start) {
+                overlay.record_frame(frame_start - last_frame_start);
+            }
+
+            if canvas.size() != size {
+                canvas = Canvas::new(GRAY, size);
+                scene_stats = draw_scene(&mut canvas);
+                scene_canvas = Canvas::from_pixels(canvas.pixels().clone());
+            } else if let Some(rect) = overlay_rect.take() {
+                canvas.draw_image_in_rect(&scene_canvas, Affine2::IDENTITY, Filter::Nearest, &rect);
             }
-            Event::WindowEvent {
-                event: WindowEvent::CloseRequested,
-                window_id,
-            } if window_id == window.id() => {
+            let (step_time, counters) = scene_stats;
+            overlay_rect = overlay.draw(&mut canvas, step_time, &counters);
+
+            if let Some(Err(err)) = recorder.as_mut().map(|recorder| recorder.present(&canvas)) {
+                eprintln!("Recording stopped: {err}");
+                recorder = None;
+            }
+
+            window.pre_present_notify();
+            if let Err(err) = target.present(&canvas) {
+                eprintln!("Presenting failed: {err}");
                 elwt.exit();
+                return;
             }
-            _ => {}
-        })
-        .unwrap();
+            canvas.clear_damage();
+
+            window.request_redraw();
+        }
+        Event::WindowEvent {
+            event:
+                WindowEvent::KeyboardInput {
+                    event:
+                        KeyEvent {
+                            physical_key: PhysicalKey::Code(KeyCode::KeyR),
+                            state: ElementState::Pressed,
+                            repeat: false,
+                            ..
+                        },
+                    ..
+                },
+            window_id,
+        } if window_id == window.id() => {
+            recorder = match recorder.take() {
+                Some(mut recorder) => {
+                    if let Err(err) = recorder.close() {
+                        eprintln!("Failed to finish recording: {err}");
+                    }
+                    None
+                }
+                None => {
+                    let secs = SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .map_or(0, |elapsed| elapsed.as_secs());
+                    let path = PathBuf::from(format!("verle-{secs}.y4m"));
+                    match create_recorder(&path, WINDOW_RECORDING_FPS) {
+                        Ok(recorder) => {
+                            eprintln!("Recording to {}", path.display());
+                            Some(Paced::new(recorder, WINDOW_RECORDING_FPS))
+                        }
+                        Err(err) => {
+                            eprintln!("{err:#}");
+                            None
+                        }
+                    }
+                }
+            };
+        }
+        Event::WindowEvent {
+            event:
+                WindowEvent::KeyboardInput {
+                    event:
+                        KeyEvent {
+                            physical_key: PhysicalKey::Code(KeyCode::F3),
+                            state: ElementState::Pressed,
+                            repeat: false,
+                            ..
+                        },
+                    ..
+                },
+            window_id,
+        } if window_id == window.id() => overlay.toggle(),
+        Event::WindowEvent {
+            event: WindowEvent::CloseRequested,
+            window_id,
+        } if window_id == window.id() => {
+            elwt.exit();
+        }
+        _ => {}
+    })?;
+    Ok(())
 }
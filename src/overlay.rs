@@ -0,0 +1,345 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+use verle::{Canvas, Draw, Font, Pos, Rect, Size};
+
+const BACKGROUND: u32 = 0x202020;
+const TEXT: u32 = 0xFFFFFF;
+const BAR: u32 = 0x40C040;
+const SLOW_BAR: u32 = 0xE04040;
+const TARGET_LINE: u32 = 0x808080;
+
+/// Frames kept for the graph, one pixel column each.
+const HISTORY: usize = 120;
+const GRAPH_HEIGHT: u32 = 40;
+/// Frame time of the target frame rate, drawn as a line across the graph.
+const TARGET_FRAME_TIME: Duration = Duration::from_micros(16_667);
+/// Frame time at the top of the graph, longer frames are cut off.
+const GRAPH_MAX: Duration = Duration::from_micros(33_333);
+const PADDING: u32 = 4;
+
+/// Debug statistics drawn into the top right corner of the canvas.
+pub struct Overlay {
+    visible: bool,
+    scene_cached: bool,
+    frame_times: VecDeque<Duration>,
+}
+
+impl Overlay {
+    pub fn new(visible: bool) -> Self {
+        Self {
+            visible,
+            scene_cached: false,
+            frame_times: VecDeque::with_capacity(HISTORY),
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    /// Marks the scene statistics as taken when the scene was last drawn rather than this frame.
+    pub fn set_scene_cached(&mut self, scene_cached: bool) {
+        self.scene_cached = scene_cached;
+    }
+
+    /// Records the duration of the last frame for the graph.
+    pub fn record_frame(&mut self, frame_time: Duration) {
+        if self.frame_times.len() == HISTORY {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(frame_time);
+    }
+
+    /// Draws the last frame time, the scene `step_time` and the `counters` above the frame time
+    /// graph, if the overlay is visible. Returns the rect covered by the overlay.
+    pub fn draw(
+        &self,
+        canvas: &mut Canvas,
+        step_time: Duration,
+        counters: &[(&str, u64)],
+    ) -> Option<Rect> {
+        if !self.visible {
+            return None;
+        }
+        let font = Font::builtin();
+        let text = self.text(step_time, counters);
+        let text_size = font.measure_text(&text, 1);
+        let size = Size::new(
+            text_size.width.max(HISTORY as u32) + 2 * PADDING,
+            text_size.height + GRAPH_HEIGHT + 3 * PADDING,
+        );
+        let canvas_size = canvas.size();
+        let Ok(rect) = Rect::try_new_size(
+            Pos::new(canvas_size.width.saturating_sub(size.width) as i32, 0),
+            size,
+        ) else {
+            return None;
+        };
+        for y in rect.top()..rect.bottom() {
+            canvas.draw_line_in_rect(
+                Pos::new(rect.left(), y),
+                Pos::new(rect.right() - 1, y),
+                BACKGROUND,
+                &rect,
+            );
+        }
+        let padding = PADDING as i32;
+        canvas.draw_text_in_rect(
+            Pos::new(rect.left() + padding, rect.top() + padding),
+            &text,
+            font,
+            TEXT,
+            1,
+            &rect,
+        );
+
+        let graph_left = rect.left() + padding;
+        let graph_bottom = rect.bottom() - padding - 1;
+        let bar_height = |time: Duration| {
+            let ratio = time.as_secs_f64() / GRAPH_MAX.as_secs_f64();
+            (ratio.min(1.0) * f64::from(GRAPH_HEIGHT - 1)).round() as i32
+        };
+        for (x, &time) in (graph_left..).zip(&self.frame_times) {
+            let color = if time > TARGET_FRAME_TIME {
+                SLOW_BAR
+            } else {
+                BAR
+            };
+            canvas.draw_line_in_rect(
+                Pos::new(x, graph_bottom),
+                Pos::new(x, graph_bottom - bar_height(time)),
+                color,
+                &rect,
+            );
+        }
+        let target_y = graph_bottom - bar_height(TARGET_FRAME_TIME);
+        canvas.draw_line_in_rect(
+            Pos::new(graph_left, target_y),
+            Pos::new(graph_left + HISTORY as i32 - 1, target_y),
+            TARGET_LINE,
+            &rect,
+        );
+        Some(rect)
+    }
+
+    fn text(&self, step_time: Duration, counters: &[(&str, u64)]) -> String {
+        let frame_time = self.frame_times.back().copied().unwrap_or_default();
+        let mut text = format!(
+            "frame {:6.2} ms {:5.0} fps\nscene {:6.2} ms",
+            millis(frame_time),
+            1.0 / frame_time.as_secs_f64().max(f64::EPSILON),
+            millis(step_time),
+        );
+        if self.scene_cached {
+            text.push_str(" at resize");
+        }
+        for (name, value) in counters {
+            text.push_str(&format!("\n{name:<5} {value:>9}"));
+        }
+        text
+    }
+}
+
+#[inline]
+fn millis(time: Duration) -> f64 {
+    time.as_secs_f64() * 1000.0
+}
+
+/// Counts the shapes drawn through it before passing them on.
+pub struct CountingDraw<'a, D> {
+    inner: &'a mut D,
+    circles: u64,
+    lines: u64,
+    dots: u64,
+}
+
+impl<'a, D: Draw> CountingDraw<'a, D> {
+    pub fn new(inner: &'a mut D) -> Self {
+        Self {
+            inner,
+            circles: 0,
+            lines: 0,
+            dots: 0,
+        }
+    }
+
+    pub fn counters(&self) -> [(&'static str, u64); 3] {
+        [
+            ("circ", self.circles),
+            ("lines", self.lines),
+            ("dots", self.dots),
+        ]
+    }
+}
+
+impl<D: Draw> Draw for CountingDraw<'_, D> {
+    fn size(&self) -> Size {
+        self.inner.size()
+    }
+
+    fn draw_circle_in_rect(
+        &mut self,
+        center: Pos,
+        radius: u32,
+        outline_color: u32,
+        fill_color: u32,
+        rect: &Rect,
+    ) {
+        self.circles += 1;
+        self.inner
+            .draw_circle_in_rect(center, radius, outline_color, fill_color, rect);
+    }
+
+    fn draw_dot_in_rect(&mut self, pos: Pos, color: u32, rect: &Rect) {
+        self.dots += 1;
+        self.inner.draw_dot_in_rect(pos, color, rect);
+    }
+
+    fn draw_line_in_rect(&mut self, from: Pos, to: Pos, color: u32, rect: &Rect) {
+        self.lines += 1;
+        self.inner.draw_line_in_rect(from, to, color, rect);
+    }
+
+    fn draw_circle(&mut self, center: Pos, radius: u32, outline_color: u32, fill_color: u32) {
+        self.circles += 1;
+        self.inner
+            .draw_circle(center, radius, outline_color, fill_color);
+    }
+
+    fn draw_dot(&mut self, pos: Pos, color: u32) {
+        self.dots += 1;
+        self.inner.draw_dot(pos, color);
+    }
+
+    fn draw_line(&mut self, from: Pos, to: Pos, color: u32) {
+        self.lines += 1;
+        self.inner.draw_line(from, to, color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pixel(canvas: &Canvas, pos: Pos) -> u32 {
+        canvas.pixels()[[pos.y as usize, pos.x as usize]]
+    }
+
+    #[test]
+    fn hidden() {
+        let mut canvas = Canvas::new(0, Size::new(320, 240));
+        let mut overlay = Overlay::new(false);
+        assert_eq!(overlay.draw(&mut canvas, Duration::ZERO, &[]), None);
+        assert!(canvas.pixels().iter().all(|&pixel| pixel == 0));
+        overlay.toggle();
+        assert!(overlay.draw(&mut canvas, Duration::ZERO, &[]).is_some());
+        overlay.toggle();
+        assert_eq!(overlay.draw(&mut canvas, Duration::ZERO, &[]), None);
+    }
+
+    #[test]
+    fn history() {
+        let mut overlay = Overlay::new(true);
+        for millis in 0..HISTORY as u64 + 5 {
+            overlay.record_frame(Duration::from_millis(millis));
+        }
+        assert_eq!(overlay.frame_times.len(), HISTORY);
+        assert_eq!(overlay.frame_times.front(), Some(&Duration::from_millis(5)));
+        assert_eq!(
+            overlay.frame_times.back(),
+            Some(&Duration::from_millis(HISTORY as u64 + 4))
+        );
+    }
+
+    #[test]
+    fn text() {
+        let mut overlay = Overlay::new(true);
+        overlay.record_frame(Duration::from_millis(20));
+        let counters = [("circ", 12), ("dots", 3)];
+        assert_eq!(
+            overlay.text(Duration::from_micros(1500), &counters),
+            "frame  20.00 ms    50 fps\nscene   1.50 ms\ncirc         12\ndots          3"
+        );
+        overlay.set_scene_cached(true);
+        assert_eq!(
+            overlay.text(Duration::from_micros(1500), &[]),
+            "frame  20.00 ms    50 fps\nscene   1.50 ms at resize"
+        );
+    }
+
+    #[test]
+    fn layout() {
+        let mut canvas = Canvas::new(0, Size::new(320, 240));
+        let mut overlay = Overlay::new(true);
+        overlay.record_frame(Duration::from_millis(40));
+        overlay.record_frame(Duration::ZERO);
+        let rect = overlay
+            .draw(&mut canvas, Duration::ZERO, &[("circ", 12)])
+            .unwrap();
+        assert_eq!((rect.top(), rect.right()), (0, 320));
+        assert!(rect.width() >= HISTORY as u32 + 2 * PADDING);
+        for (y, row) in canvas.pixels().outer_iter().enumerate() {
+            for (x, &pixel) in row.iter().enumerate() {
+                let inside = rect.contains(Pos::new(x as i32, y as i32));
+                assert_eq!(pixel != 0, inside, "at {x}, {y}");
+            }
+        }
+
+        let padding = PADDING as i32;
+        let (left, bottom) = (rect.left() + padding, rect.bottom() - padding - 1);
+        let top = bottom - (GRAPH_HEIGHT as i32 - 1);
+        assert_eq!(pixel(&canvas, Pos::new(left, bottom)), SLOW_BAR);
+        assert_eq!(pixel(&canvas, Pos::new(left, top)), SLOW_BAR);
+        assert_eq!(pixel(&canvas, Pos::new(left, top - 1)), BACKGROUND);
+        assert_eq!(pixel(&canvas, Pos::new(left + 1, bottom)), BAR);
+        assert_eq!(pixel(&canvas, Pos::new(left + 1, bottom - 1)), BACKGROUND);
+        let target_y = (top..bottom)
+            .find(|&y| pixel(&canvas, Pos::new(left + 1, y)) == TARGET_LINE)
+            .unwrap();
+        assert_eq!(
+            pixel(&canvas, Pos::new(left + HISTORY as i32 - 1, target_y)),
+            TARGET_LINE
+        );
+        assert_eq!(
+            pixel(&canvas, Pos::new(left + HISTORY as i32, target_y)),
+            BACKGROUND
+        );
+    }
+
+    #[test]
+    fn clipped_to_small_canvas() {
+        let mut canvas = Canvas::new(0, Size::new(40, 10));
+        let mut overlay = Overlay::new(true);
+        overlay.record_frame(Duration::from_millis(40));
+        let rect = overlay.draw(&mut canvas, Duration::ZERO, &[]).unwrap();
+        assert_eq!(rect.top_left(), Pos::new(0, 0));
+        assert!(rect.contains_rect(&canvas.rect()));
+        assert!(canvas.pixels().iter().all(|&pixel| pixel != 0));
+    }
+
+    #[test]
+    fn counting_draw() {
+        let size = Size::new(40, 30);
+        let rect = Rect::try_new(Pos::new(5, 5), Pos::new(30, 20)).unwrap();
+        let draw = |draw: &mut dyn Draw| {
+            draw.draw_circle(Pos::new(10, 10), 5, 1, 2);
+            draw.draw_circle_in_rect(Pos::new(20, 10), 8, 3, 4, &rect);
+            draw.draw_line(Pos::new(0, 0), Pos::new(39, 29), 5);
+            draw.draw_line_in_rect(Pos::new(0, 29), Pos::new(39, 0), 6, &rect);
+            draw.draw_dot(Pos::new(1, 2), 7);
+            draw.draw_dot_in_rect(Pos::new(6, 6), 8, &rect);
+            draw.draw_dot(Pos::new(3, 4), 9);
+        };
+        let mut expected = Canvas::new(0, size);
+        draw(&mut expected);
+        let mut canvas = Canvas::new(0, size);
+        let mut counting = CountingDraw::new(&mut canvas);
+        assert_eq!(counting.size(), size);
+        draw(&mut counting);
+        assert_eq!(
+            counting.counters(),
+            [("circ", 2), ("lines", 2), ("dots", 3)]
+        );
+        assert_eq!(canvas.pixels(), expected.pixels());
+    }
+}
@@ -0,0 +1,78 @@
+use std::time::{Duration, Instant};
+use verle::target::RenderTarget;
+use verle::{Canvas, Error};
+
+/// Presents to `inner` at a fixed frame rate however often it is called: the last frame is
+/// repeated when calls are slower than the rate and frames are dropped when they are faster.
+pub struct Paced<T> {
+    inner: T,
+    fps: u32,
+    start: Instant,
+    frames: u64,
+}
+
+impl<T: RenderTarget> Paced<T> {
+    /// Starts the first frame now.
+    pub fn new(inner: T, fps: u32) -> Self {
+        Self {
+            inner,
+            fps,
+            start: Instant::now(),
+            frames: 0,
+        }
+    }
+
+    /// Presents `canvas` for every frame started by `now` that was not presented yet.
+    fn present_at(&mut self, canvas: &Canvas, now: Instant) -> Result<(), Error> {
+        let elapsed = now.saturating_duration_since(self.start);
+        let started = elapsed.as_nanos() * u128::from(self.fps) / Duration::from_secs(1).as_nanos();
+        let due = u64::try_from(started).unwrap_or(u64::MAX).saturating_add(1);
+        while self.frames < due {
+            self.inner.present(canvas)?;
+            self.frames += 1;
+        }
+        Ok(())
+    }
+}
+
+impl<T: RenderTarget> RenderTarget for Paced<T> {
+    fn present(&mut self, canvas: &Canvas) -> Result<(), Error> {
+        self.present_at(canvas, Instant::now())
+    }
+
+    fn close(&mut self) -> Result<(), Error> {
+        self.inner.close()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use verle::Size;
+
+    #[derive(Default)]
+    struct Frames(Vec<u32>);
+
+    impl RenderTarget for Frames {
+        fn present(&mut self, canvas: &Canvas) -> Result<(), Error> {
+            self.0.push(canvas.pixels()[[0, 0]]);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn repeats_and_drops_frames() {
+        let mut paced = Paced::new(Frames::default(), 10);
+        let start = paced.start;
+        let at = |millis| start + Duration::from_millis(millis);
+        let frame = |color| Canvas::new(color, Size::new(1, 1));
+        paced.present_at(&frame(1), at(0)).unwrap();
+        // faster than 10 fps: dropped until the next frame starts at 100 ms
+        paced.present_at(&frame(2), at(40)).unwrap();
+        paced.present_at(&frame(3), at(99)).unwrap();
+        paced.present_at(&frame(4), at(100)).unwrap();
+        // slower: frames 2 to 4 all show the latest canvas
+        paced.present_at(&frame(5), at(450)).unwrap();
+        assert_eq!(paced.inner.0, [1, 4, 5, 5, 5]);
+    }
+}
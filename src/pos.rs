@@ -1,5 +1,11 @@
-use glam::IVec2;
+use crate::Size;
+use glam::{IVec2, Vec2};
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
 
+/// A pixel position.
+///
+/// The operators behave like the ones of `i32`: they panic on overflow in debug builds and wrap
+/// in release builds. The `checked_*` and `saturating_*` methods never panic.
 #[derive(Eq, PartialEq, Debug, Copy, Clone, Default)]
 pub struct Pos {
     pub x: i32,
@@ -11,6 +17,170 @@ impl Pos {
     pub fn new(x: i32, y: i32) -> Self {
         Self { x, y }
     }
+
+    #[inline]
+    pub fn min(self, other: Pos) -> Pos {
+        Pos::new(self.x.min(other.x), self.y.min(other.y))
+    }
+
+    #[inline]
+    pub fn max(self, other: Pos) -> Pos {
+        Pos::new(self.x.max(other.x), self.y.max(other.y))
+    }
+
+    /// Clamps both coordinates, panics if `min` is greater than `max` in either of them.
+    #[inline]
+    pub fn clamp(self, min: Pos, max: Pos) -> Pos {
+        Pos::new(self.x.clamp(min.x, max.x), self.y.clamp(min.y, max.y))
+    }
+
+    /// Steps between the positions along the axes, which does not fit `u32` for the farthest
+    /// apart positions.
+    #[inline]
+    pub fn manhattan_distance(self, other: Pos) -> u64 {
+        u64::from(self.x.abs_diff(other.x)) + u64::from(self.y.abs_diff(other.y))
+    }
+
+    #[inline]
+    pub fn euclidean_distance(self, other: Pos) -> f64 {
+        f64::from(self.x.abs_diff(other.x)).hypot(f64::from(self.y.abs_diff(other.y)))
+    }
+
+    #[inline]
+    pub fn checked_add(self, other: Pos) -> Option<Pos> {
+        Some(Pos::new(
+            self.x.checked_add(other.x)?,
+            self.y.checked_add(other.y)?,
+        ))
+    }
+
+    #[inline]
+    pub fn checked_sub(self, other: Pos) -> Option<Pos> {
+        Some(Pos::new(
+            self.x.checked_sub(other.x)?,
+            self.y.checked_sub(other.y)?,
+        ))
+    }
+
+    #[inline]
+    pub fn checked_mul(self, factor: i32) -> Option<Pos> {
+        Some(Pos::new(
+            self.x.checked_mul(factor)?,
+            self.y.checked_mul(factor)?,
+        ))
+    }
+
+    #[inline]
+    pub fn checked_neg(self) -> Option<Pos> {
+        Some(Pos::new(self.x.checked_neg()?, self.y.checked_neg()?))
+    }
+
+    #[inline]
+    pub fn checked_add_size(self, size: Size) -> Option<Pos> {
+        Some(Pos::new(
+            self.x.checked_add_unsigned(size.width)?,
+            self.y.checked_add_unsigned(size.height)?,
+        ))
+    }
+
+    #[inline]
+    pub fn saturating_add(self, other: Pos) -> Pos {
+        Pos::new(
+            self.x.saturating_add(other.x),
+            self.y.saturating_add(other.y),
+        )
+    }
+
+    #[inline]
+    pub fn saturating_sub(self, other: Pos) -> Pos {
+        Pos::new(
+            self.x.saturating_sub(other.x),
+            self.y.saturating_sub(other.y),
+        )
+    }
+
+    #[inline]
+    pub fn saturating_mul(self, factor: i32) -> Pos {
+        Pos::new(self.x.saturating_mul(factor), self.y.saturating_mul(factor))
+    }
+
+    #[inline]
+    pub fn saturating_neg(self) -> Pos {
+        Pos::new(self.x.saturating_neg(), self.y.saturating_neg())
+    }
+
+    #[inline]
+    pub fn saturating_add_size(self, size: Size) -> Pos {
+        Pos::new(
+            self.x.saturating_add_unsigned(size.width),
+            self.y.saturating_add_unsigned(size.height),
+        )
+    }
+}
+
+impl Add for Pos {
+    type Output = Pos;
+
+    #[inline]
+    fn add(self, rhs: Pos) -> Self::Output {
+        Pos::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl Sub for Pos {
+    type Output = Pos;
+
+    #[inline]
+    fn sub(self, rhs: Pos) -> Self::Output {
+        Pos::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl Neg for Pos {
+    type Output = Pos;
+
+    #[inline]
+    fn neg(self) -> Self::Output {
+        Pos::new(-self.x, -self.y)
+    }
+}
+
+impl Mul<i32> for Pos {
+    type Output = Pos;
+
+    #[inline]
+    fn mul(self, rhs: i32) -> Self::Output {
+        Pos::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+impl Add<Size> for Pos {
+    type Output = Pos;
+
+    /// Moves by the size, e.g. from the top left to the bottom right corner of a rect.
+    #[inline]
+    fn add(self, rhs: Size) -> Self::Output {
+        let add = |value: i32, amount: u32| {
+            let (sum, overflow) = value.overflowing_add_unsigned(amount);
+            debug_assert!(!overflow, "attempt to add with overflow");
+            sum
+        };
+        Pos::new(add(self.x, rhs.width), add(self.y, rhs.height))
+    }
+}
+
+impl AddAssign for Pos {
+    #[inline]
+    fn add_assign(&mut self, rhs: Pos) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for Pos {
+    #[inline]
+    fn sub_assign(&mut self, rhs: Pos) {
+        *self = *self - rhs;
+    }
 }
 
 impl From<Pos> for IVec2 {
@@ -27,6 +197,36 @@ impl From<IVec2> for Pos {
     }
 }
 
+impl From<(i32, i32)> for Pos {
+    #[inline]
+    fn from((x, y): (i32, i32)) -> Self {
+        Self::new(x, y)
+    }
+}
+
+impl From<Pos> for (i32, i32) {
+    #[inline]
+    fn from(pos: Pos) -> Self {
+        (pos.x, pos.y)
+    }
+}
+
+/// Coordinates beyond ±2²⁴ are rounded to the nearest `f32`.
+impl From<Pos> for Vec2 {
+    #[inline]
+    fn from(pos: Pos) -> Self {
+        Self::new(pos.x as f32, pos.y as f32)
+    }
+}
+
+/// The pixel containing the point, saturating at the bounds of `i32`. NaN maps to 0.
+impl From<Vec2> for Pos {
+    #[inline]
+    fn from(vec2: Vec2) -> Self {
+        Self::new(vec2.x.floor() as i32, vec2.y.floor() as i32)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -45,4 +245,133 @@ mod tests {
     fn pos_from_ivec2() {
         assert_eq!(Pos::new(1, 2), IVec2::new(1, 2).into());
     }
+
+    #[test]
+    fn tuples() {
+        assert_eq!(Pos::from((1, -2)), Pos::new(1, -2));
+        assert_eq!(<(i32, i32)>::from(Pos::new(1, -2)), (1, -2));
+    }
+
+    #[test]
+    fn vec2() {
+        assert_eq!(Vec2::from(Pos::new(1, -2)), Vec2::new(1.0, -2.0));
+        assert_eq!(Pos::from(Vec2::new(1.5, -1.5)), Pos::new(1, -2));
+        assert_eq!(
+            Pos::from(Vec2::new(f32::INFINITY, -1e20)),
+            Pos::new(i32::MAX, i32::MIN)
+        );
+        assert_eq!(Pos::from(Vec2::new(f32::NAN, 0.0)), Pos::new(0, 0));
+    }
+
+    #[test]
+    fn operators() {
+        let mut pos = Pos::new(1, 2) + Pos::new(3, -5);
+        assert_eq!(pos, Pos::new(4, -3));
+        assert_eq!(pos - Pos::new(5, 5), Pos::new(-1, -8));
+        assert_eq!(-pos, Pos::new(-4, 3));
+        assert_eq!(pos * -2, Pos::new(-8, 6));
+        assert_eq!(pos + Size::new(2, 3), Pos::new(6, 0));
+        pos -= Pos::new(1, 1);
+        pos += Pos::new(0, 10);
+        assert_eq!(pos, Pos::new(3, 6));
+    }
+
+    #[test]
+    fn add_size_beyond_i32() {
+        assert_eq!(
+            Pos::new(i32::MIN, -1) + Size::new(u32::MAX, 1),
+            Pos::new(i32::MAX, 0)
+        );
+    }
+
+    #[test]
+    #[cfg_attr(debug_assertions, should_panic(expected = "overflow"))]
+    fn add_overflow() {
+        assert_eq!(
+            Pos::new(i32::MAX, 0) + Pos::new(1, 0),
+            Pos::new(i32::MIN, 0)
+        );
+    }
+
+    #[test]
+    #[cfg_attr(debug_assertions, should_panic(expected = "overflow"))]
+    fn neg_overflow() {
+        assert_eq!(-Pos::new(i32::MIN, 0), Pos::new(i32::MIN, 0));
+    }
+
+    #[test]
+    #[cfg_attr(debug_assertions, should_panic(expected = "overflow"))]
+    fn add_size_overflow() {
+        assert_eq!(Pos::new(1, 0) + Size::new(u32::MAX, 0), Pos::new(0, 0));
+    }
+
+    #[test]
+    fn checked() {
+        assert_eq!(
+            Pos::new(1, 2).checked_add(Pos::new(3, 4)),
+            Some(Pos::new(4, 6))
+        );
+        assert_eq!(Pos::new(1, i32::MAX).checked_add(Pos::new(0, 1)), None);
+        assert_eq!(Pos::new(i32::MIN, 0).checked_sub(Pos::new(1, 0)), None);
+        assert_eq!(Pos::new(i32::MAX, 1).checked_mul(2), None);
+        assert_eq!(Pos::new(3, -1).checked_mul(-2), Some(Pos::new(-6, 2)));
+        assert_eq!(Pos::new(0, i32::MIN).checked_neg(), None);
+        assert_eq!(
+            Pos::new(i32::MIN, 0).checked_add_size(Size::new(u32::MAX, 0)),
+            Some(Pos::new(i32::MAX, 0))
+        );
+        assert_eq!(
+            Pos::new(0, 1).checked_add_size(Size::new(0, u32::MAX)),
+            None
+        );
+    }
+
+    #[test]
+    fn saturating() {
+        assert_eq!(
+            Pos::new(i32::MAX, i32::MIN).saturating_add(Pos::new(1, -1)),
+            Pos::new(i32::MAX, i32::MIN)
+        );
+        assert_eq!(
+            Pos::new(i32::MIN, 0).saturating_sub(Pos::new(1, 1)),
+            Pos::new(i32::MIN, -1)
+        );
+        assert_eq!(
+            Pos::new(i32::MAX / 2 + 1, -3).saturating_mul(2),
+            Pos::new(i32::MAX, -6)
+        );
+        assert_eq!(
+            Pos::new(i32::MIN, 1).saturating_neg(),
+            Pos::new(i32::MAX, -1)
+        );
+        assert_eq!(
+            Pos::new(0, -1).saturating_add_size(Size::new(u32::MAX, 1)),
+            Pos::new(i32::MAX, 0)
+        );
+    }
+
+    #[test]
+    fn min_max_clamp() {
+        let (a, b) = (Pos::new(1, 5), Pos::new(3, -2));
+        assert_eq!(a.min(b), Pos::new(1, -2));
+        assert_eq!(a.max(b), Pos::new(3, 5));
+        assert_eq!(
+            Pos::new(-4, 9).clamp(Pos::new(0, 0), Pos::new(5, 5)),
+            Pos::new(0, 5)
+        );
+    }
+
+    #[test]
+    fn distance() {
+        assert_eq!(Pos::new(1, 2).manhattan_distance(Pos::new(4, -2)), 7);
+        assert_eq!(Pos::new(1, 2).euclidean_distance(Pos::new(4, -2)), 5.0);
+        assert_eq!(
+            Pos::new(i32::MIN, i32::MIN).manhattan_distance(Pos::new(i32::MAX, i32::MAX)),
+            2 * u64::from(u32::MAX)
+        );
+        assert_eq!(
+            Pos::new(i32::MIN, 0).euclidean_distance(Pos::new(i32::MAX, 0)),
+            f64::from(u32::MAX)
+        );
+    }
 }
@@ -1,8 +1,7 @@
 use crate::Pos;
 use crate::Size;
-use glam::{IVec2, UVec2};
 use std::fmt::{Display, Formatter};
-use std::ops::BitAnd;
+use std::ops::{BitAnd, BitOr};
 use thiserror::Error;
 
 #[derive(Eq, PartialEq, Debug, Copy, Clone, Default)]
@@ -38,13 +37,30 @@ impl Rect {
         }
     }
 
+    /// Creates a rect from its top left corner and size. Both dimensions must fit `i32` and the
+    /// bottom right corner must not overflow.
     #[inline]
     pub fn try_new_size(top_left: Pos, size: Size) -> Result<Self, Error> {
+        if i32::try_from(size.width).is_err() || i32::try_from(size.height).is_err() {
+            return Err(Error::TryFromIntError(size));
+        }
+        let end = |dimension, value: i32, length: u32| {
+            value
+                .checked_add_unsigned(length)
+                .ok_or(Error::CoordinateOverflow(
+                    dimension,
+                    Overflow {
+                        value,
+                        offset: i64::from(length),
+                    },
+                ))
+        };
         Ok(Self {
             top_left,
-            bottom_right: (IVec2::from(top_left)
-                + IVec2::try_from(UVec2::from(size)).map_err(|_| Error::TryFromIntError(size))?)
-            .into(),
+            bottom_right: Pos::new(
+                end(Dimension::X, top_left.x, size.width)?,
+                end(Dimension::Y, top_left.y, size.height)?,
+            ),
         })
     }
 
@@ -88,19 +104,21 @@ impl Rect {
         self.bottom_right.y
     }
 
+    /// Unsigned, a rect spanning all of `i32` is `u32::MAX` wide.
     #[inline]
-    pub fn width(&self) -> i32 {
-        self.right() - self.left()
+    pub fn width(&self) -> u32 {
+        self.right().abs_diff(self.left())
     }
 
+    /// Unsigned, a rect spanning all of `i32` is `u32::MAX` tall.
     #[inline]
-    pub fn height(&self) -> i32 {
-        self.bottom() - self.top()
+    pub fn height(&self) -> u32 {
+        self.bottom().abs_diff(self.top())
     }
 
     #[inline]
     pub fn size(&self) -> Size {
-        Size::new(self.width() as u32, self.height() as u32)
+        Size::new(self.width(), self.height())
     }
 
     #[inline]
@@ -108,22 +126,219 @@ impl Rect {
         (self.left()..self.right()).is_empty() || (self.top()..self.bottom()).is_empty()
     }
 
+    #[inline]
+    pub fn area(&self) -> u64 {
+        u64::from(self.size().width) * u64::from(self.size().height)
+    }
+
+    /// The smallest rect containing both rects. Zero size rects cover no pixels and are ignored.
+    #[inline]
+    pub fn union(&self, other: &Rect) -> Rect {
+        if other.is_zero_size() {
+            *self
+        } else if self.is_zero_size() {
+            *other
+        } else {
+            Rect {
+                top_left: self.top_left.min(other.top_left),
+                bottom_right: self.bottom_right.max(other.bottom_right),
+            }
+        }
+    }
+
     #[inline]
     pub fn contains(&self, pos: Pos) -> bool {
         (self.left()..self.right()).contains(&pos.x) && (self.top()..self.bottom()).contains(&pos.y)
     }
+
+    /// Whether `other` lies within the bounds of this rect.
+    #[inline]
+    pub fn contains_rect(&self, other: &Rect) -> bool {
+        self.left() <= other.left()
+            && self.top() <= other.top()
+            && other.right() <= self.right()
+            && other.bottom() <= self.bottom()
+    }
+
+    /// Whether the rects share at least one pixel.
+    #[inline]
+    pub fn intersects(&self, other: &Rect) -> bool {
+        (self & other).is_some_and(|intersection| !intersection.is_zero_size())
+    }
+
+    /// The center pixel, rounded towards the top left.
+    #[inline]
+    pub fn center(&self) -> Pos {
+        let middle = |low: i32, high: i32| (i64::from(low) + i64::from(high) - 1).div_euclid(2);
+        Pos::new(
+            middle(self.left(), self.right()).max(i64::from(self.left())) as i32,
+            middle(self.top(), self.bottom()).max(i64::from(self.top())) as i32,
+        )
+    }
+
+    /// Moves the rect by `offset`, failing if a corner leaves the range of `i32`.
+    #[inline]
+    pub fn try_translate(&self, offset: Pos) -> Result<Rect, Error> {
+        let shift = |dimension, value: i32, offset: i32| {
+            value.checked_add(offset).ok_or(Error::CoordinateOverflow(
+                dimension,
+                Overflow {
+                    value,
+                    offset: i64::from(offset),
+                },
+            ))
+        };
+        Ok(Rect {
+            top_left: Pos::new(
+                shift(Dimension::X, self.left(), offset.x)?,
+                shift(Dimension::Y, self.top(), offset.y)?,
+            ),
+            bottom_right: Pos::new(
+                shift(Dimension::X, self.right(), offset.x)?,
+                shift(Dimension::Y, self.bottom(), offset.y)?,
+            ),
+        })
+    }
+
+    /// Moves every side `amount` pixels inwards. Sides meet in the middle rather than cross.
+    #[inline]
+    pub fn inset(&self, amount: u32) -> Rect {
+        let inset = |low: i32, high: i32| {
+            let (low, high) = (i64::from(low), i64::from(high));
+            let amount = i64::from(amount).min((high - low) / 2);
+            ((low + amount) as i32, (high - amount) as i32)
+        };
+        let (left, right) = inset(self.left(), self.right());
+        let (top, bottom) = inset(self.top(), self.bottom());
+        Rect {
+            top_left: Pos::new(left, top),
+            bottom_right: Pos::new(right, bottom),
+        }
+    }
+
+    /// Moves every side `amount` pixels outwards, saturating at the bounds of `i32`.
+    #[inline]
+    pub fn outset(&self, amount: u32) -> Rect {
+        let amount = amount.min(i32::MAX as u32) as i32;
+        Rect {
+            top_left: Pos::new(
+                self.left().saturating_sub(amount),
+                self.top().saturating_sub(amount),
+            ),
+            bottom_right: Pos::new(
+                self.right().saturating_add(amount),
+                self.bottom().saturating_add(amount),
+            ),
+        }
+    }
+
+    /// Splits into the columns left of `x` and from `x` on, `x` is clamped into the rect.
+    #[inline]
+    pub fn split_at_x(&self, x: i32) -> (Rect, Rect) {
+        let x = x.clamp(self.left(), self.right());
+        (
+            Rect {
+                top_left: self.top_left,
+                bottom_right: Pos::new(x, self.bottom()),
+            },
+            Rect {
+                top_left: Pos::new(x, self.top()),
+                bottom_right: self.bottom_right,
+            },
+        )
+    }
+
+    /// Splits into the rows above `y` and from `y` on, `y` is clamped into the rect.
+    #[inline]
+    pub fn split_at_y(&self, y: i32) -> (Rect, Rect) {
+        let y = y.clamp(self.top(), self.bottom());
+        (
+            Rect {
+                top_left: self.top_left,
+                bottom_right: Pos::new(self.right(), y),
+            },
+            Rect {
+                top_left: Pos::new(self.left(), y),
+                bottom_right: self.bottom_right,
+            },
+        )
+    }
+
+    /// Left and right half, the right one is a column wider for odd widths.
+    #[inline]
+    pub fn halves_x(&self) -> (Rect, Rect) {
+        self.split_at_x(Self::grid_line(self.left(), self.right(), 1, 2))
+    }
+
+    /// Top and bottom half, the bottom one is a row taller for odd heights.
+    #[inline]
+    pub fn halves_y(&self) -> (Rect, Rect) {
+        self.split_at_y(Self::grid_line(self.top(), self.bottom(), 1, 2))
+    }
+
+    /// Splits into `columns` x `rows` cells, row by row. Cells differ by at most one pixel in
+    /// width and height.
+    pub fn grid(&self, columns: u32, rows: u32) -> impl Iterator<Item = Rect> + '_ {
+        (0..rows).flat_map(move |row| {
+            let top = Self::grid_line(self.top(), self.bottom(), row, rows);
+            let bottom = Self::grid_line(self.top(), self.bottom(), row + 1, rows);
+            (0..columns).map(move |column| Rect {
+                top_left: Pos::new(
+                    Self::grid_line(self.left(), self.right(), column, columns),
+                    top,
+                ),
+                bottom_right: Pos::new(
+                    Self::grid_line(self.left(), self.right(), column + 1, columns),
+                    bottom,
+                ),
+            })
+        })
+    }
+
+    /// The line `index` of `count` equal parts of `low..high`. The product of the length and
+    /// `index` needs all 64 bits, so it is unsigned.
+    #[inline]
+    fn grid_line(low: i32, high: i32, index: u32, count: u32) -> i32 {
+        let length = u64::from(high.abs_diff(low));
+        let offset = length * u64::from(index) / u64::from(count);
+        (i64::from(low) + offset as i64) as i32
+    }
+
+    /// The parts of this rect outside `other`: up to four rects, the full width band above
+    /// `other`, the parts left and right of it and the full width band below it.
+    pub fn subtract(&self, other: &Rect) -> impl Iterator<Item = Rect> {
+        let parts = match self & other {
+            Some(inner) if !inner.is_zero_size() => {
+                let (above, rest) = self.split_at_y(inner.top());
+                let (middle, below) = rest.split_at_y(inner.bottom());
+                let (left, rest) = middle.split_at_x(inner.left());
+                let (_, right) = rest.split_at_x(inner.right());
+                [above, left, right, below]
+            }
+            _ => [*self, Rect::default(), Rect::default(), Rect::default()],
+        };
+        parts.into_iter().filter(|part| !part.is_zero_size())
+    }
+}
+
+impl BitOr<&Rect> for &Rect {
+    type Output = Rect;
+
+    #[inline]
+    fn bitor(self, rhs: &Rect) -> Self::Output {
+        self.union(rhs)
+    }
 }
 
+#[allow(clippy::needless_lifetimes)]
 impl<'a, 'b> BitAnd<&'a Rect> for &'b Rect {
     type Output = Option<Rect>;
 
     #[inline]
     fn bitand(self, rhs: &Rect) -> Self::Output {
         Rect::try_new(
-            IVec2::from(self.top_left).max(rhs.top_left.into()).into(),
-            IVec2::from(self.bottom_right)
-                .min(rhs.bottom_right.into())
-                .into(),
+            self.top_left.max(rhs.top_left),
+            self.bottom_right.min(rhs.bottom_right),
         )
         .ok()
     }
@@ -141,6 +356,12 @@ pub struct InvalidActual {
     pub higher: i32,
 }
 
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct Overflow {
+    pub value: i32,
+    pub offset: i64,
+}
+
 impl Display for Dimension {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -160,11 +381,14 @@ pub enum Error {
     InvalidDimensionValue(Dimension, InvalidActual),
     #[error("Can not cast u32 value to i32: {0}")]
     TryFromIntError(Size),
+    #[error("Coordinate overflow in dimension {0}: {} + {} does not fit i32", .1.value, .1.offset)]
+    CoordinateOverflow(Dimension, Overflow),
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     #[test]
     fn new_success() {
@@ -240,6 +464,29 @@ mod tests {
         )
     }
 
+    #[test]
+    fn new_overflow_err() {
+        let err = Rect::try_new_size(Pos::new(1, i32::MAX - 1), Size::new(3, 2)).unwrap_err();
+        assert_eq!(
+            err,
+            Error::CoordinateOverflow(
+                Dimension::Y,
+                Overflow {
+                    value: i32::MAX - 1,
+                    offset: 2
+                }
+            )
+        );
+        assert_eq!(
+            err.to_string(),
+            "Coordinate overflow in dimension Y: 2147483646 + 2 does not fit i32"
+        );
+        assert_eq!(
+            Rect::try_new_size(Pos::new(i32::MAX - 3, 0), Size::new(3, 0)).map(|rect| rect.right()),
+            Ok(i32::MAX)
+        );
+    }
+
     #[test]
     fn top_left() {
         assert_eq!(
@@ -323,6 +570,27 @@ mod tests {
         assert!(intersection.unwrap().is_zero_size());
     }
 
+    #[test]
+    fn union() {
+        let union = Rect::try_new_size(Pos::new(2, 2), Size::new(2, 1))
+            .unwrap()
+            .union(&Rect::try_new_size(Pos::new(3, 1), Size::new(3, 3)).unwrap());
+        assert_eq!(
+            union,
+            Rect::try_new_size(Pos::new(2, 1), Size::new(4, 3)).unwrap()
+        );
+        assert_eq!(union.area(), 12);
+    }
+
+    #[test]
+    fn zero_size_union() {
+        let rect = Rect::try_new_size(Pos::new(2, 2), Size::new(2, 1)).unwrap();
+        let zero = Rect::try_new_size(Pos::new(-5, 9), Size::new(0, 3)).unwrap();
+        assert_eq!(rect.union(&zero), rect);
+        assert_eq!(zero.union(&rect), rect);
+        assert_eq!(zero.area(), 0);
+    }
+
     #[test]
     fn top_right() {
         assert_eq!(
@@ -462,4 +730,271 @@ mod tests {
         }
         .contains(Pos::new(3, 3)));
     }
+
+    #[test]
+    fn union_operator() {
+        let union = &Rect::try_new_size(Pos::new(-1, 2), Size::new(2, 1)).unwrap()
+            | &Rect::try_new_size(Pos::new(3, 1), Size::new(3, 3)).unwrap();
+        assert_eq!(
+            union,
+            Rect::try_new(Pos::new(-1, 1), Pos::new(6, 4)).unwrap()
+        );
+    }
+
+    #[test]
+    fn contains_rect() {
+        let rect = Rect::try_new(Pos::new(1, 1), Pos::new(5, 4)).unwrap();
+        assert!(rect.contains_rect(&rect));
+        assert!(rect.contains_rect(&Rect::try_new(Pos::new(2, 1), Pos::new(5, 3)).unwrap()));
+        assert!(!rect.contains_rect(&Rect::try_new(Pos::new(0, 1), Pos::new(5, 3)).unwrap()));
+        assert!(!rect.contains_rect(&Rect::try_new(Pos::new(2, 2), Pos::new(6, 3)).unwrap()));
+    }
+
+    #[test]
+    fn intersects() {
+        let rect = Rect::try_new(Pos::new(1, 1), Pos::new(3, 3)).unwrap();
+        assert!(rect.intersects(&Rect::try_new(Pos::new(2, 2), Pos::new(4, 4)).unwrap()));
+        assert!(!rect.intersects(&Rect::try_new(Pos::new(3, 1), Pos::new(4, 3)).unwrap()));
+        assert!(!rect.intersects(&Rect::try_new(Pos::new(5, 5), Pos::new(6, 6)).unwrap()));
+    }
+
+    #[test]
+    fn center() {
+        assert_eq!(
+            Rect::try_new(Pos::new(1, 2), Pos::new(4, 6))
+                .unwrap()
+                .center(),
+            Pos::new(2, 3)
+        );
+        assert_eq!(
+            Rect::try_new(Pos::new(-3, -3), Pos::new(-3, -1))
+                .unwrap()
+                .center(),
+            Pos::new(-3, -3)
+        );
+        assert_eq!(
+            Rect::try_new(Pos::new(i32::MIN, i32::MIN), Pos::new(i32::MAX, i32::MAX))
+                .unwrap()
+                .center(),
+            Pos::new(-1, -1)
+        );
+    }
+
+    #[test]
+    fn translate() {
+        assert_eq!(
+            Rect::try_new(Pos::new(1, 2), Pos::new(3, 4))
+                .unwrap()
+                .try_translate(Pos::new(-2, 5)),
+            Rect::try_new(Pos::new(-1, 7), Pos::new(1, 9))
+        );
+        assert_eq!(
+            Rect::try_new(Pos::new(-5, 0), Pos::new(-1, 4))
+                .unwrap()
+                .try_translate(Pos::new(i32::MIN + 2, 0)),
+            Err(Error::CoordinateOverflow(
+                Dimension::X,
+                Overflow {
+                    value: -5,
+                    offset: i64::from(i32::MIN + 2)
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn inset() {
+        let rect = Rect::try_new(Pos::new(0, 0), Pos::new(10, 5)).unwrap();
+        assert_eq!(
+            rect.inset(1),
+            Rect::try_new(Pos::new(1, 1), Pos::new(9, 4)).unwrap()
+        );
+        assert_eq!(
+            rect.inset(3),
+            Rect::try_new(Pos::new(3, 2), Pos::new(7, 3)).unwrap()
+        );
+        assert!(rect.inset(u32::MAX).is_zero_size());
+    }
+
+    #[test]
+    fn outset() {
+        let rect = Rect::try_new(Pos::new(0, 0), Pos::new(10, 5)).unwrap();
+        assert_eq!(
+            rect.outset(2),
+            Rect::try_new(Pos::new(-2, -2), Pos::new(12, 7)).unwrap()
+        );
+        assert_eq!(
+            rect.outset(u32::MAX),
+            Rect::try_new(Pos::new(-i32::MAX, -i32::MAX), Pos::new(i32::MAX, i32::MAX)).unwrap()
+        );
+    }
+
+    #[test]
+    fn split_at() {
+        let rect = Rect::try_new(Pos::new(0, 0), Pos::new(4, 3)).unwrap();
+        assert_eq!(
+            rect.split_at_x(1),
+            (
+                Rect::try_new(Pos::new(0, 0), Pos::new(1, 3)).unwrap(),
+                Rect::try_new(Pos::new(1, 0), Pos::new(4, 3)).unwrap()
+            )
+        );
+        assert_eq!(
+            rect.split_at_y(5),
+            (rect, Rect::try_new(Pos::new(0, 3), Pos::new(4, 3)).unwrap())
+        );
+    }
+
+    #[test]
+    fn halves() {
+        let rect = Rect::try_new(Pos::new(0, 0), Pos::new(4, 3)).unwrap();
+        assert_eq!(rect.halves_x(), rect.split_at_x(2));
+        assert_eq!(rect.halves_y(), rect.split_at_y(1));
+    }
+
+    #[test]
+    fn grid() {
+        let rect = Rect::try_new(Pos::new(0, 0), Pos::new(5, 2)).unwrap();
+        assert_eq!(
+            rect.grid(2, 2).collect::<Vec<_>>(),
+            [
+                Rect::try_new(Pos::new(0, 0), Pos::new(2, 1)).unwrap(),
+                Rect::try_new(Pos::new(2, 0), Pos::new(5, 1)).unwrap(),
+                Rect::try_new(Pos::new(0, 1), Pos::new(2, 2)).unwrap(),
+                Rect::try_new(Pos::new(2, 1), Pos::new(5, 2)).unwrap(),
+            ]
+        );
+        assert_eq!(rect.grid(0, 3).count(), 0);
+    }
+
+    #[test]
+    fn subtract() {
+        let rect = Rect::try_new(Pos::new(0, 0), Pos::new(4, 4)).unwrap();
+        assert_eq!(
+            rect.subtract(&Rect::try_new(Pos::new(1, 1), Pos::new(3, 2)).unwrap())
+                .collect::<Vec<_>>(),
+            [
+                Rect::try_new(Pos::new(0, 0), Pos::new(4, 1)).unwrap(),
+                Rect::try_new(Pos::new(0, 1), Pos::new(1, 2)).unwrap(),
+                Rect::try_new(Pos::new(3, 1), Pos::new(4, 2)).unwrap(),
+                Rect::try_new(Pos::new(0, 2), Pos::new(4, 4)).unwrap(),
+            ]
+        );
+        assert_eq!(
+            rect.subtract(&Rect::try_new(Pos::new(2, -1), Pos::new(5, 5)).unwrap())
+                .collect::<Vec<_>>(),
+            [Rect::try_new(Pos::new(0, 0), Pos::new(2, 4)).unwrap()]
+        );
+        assert_eq!(rect.subtract(&rect.outset(1)).count(), 0);
+        assert_eq!(
+            rect.subtract(&Rect::try_new(Pos::new(4, 0), Pos::new(5, 4)).unwrap())
+                .collect::<Vec<_>>(),
+            [rect]
+        );
+    }
+
+    #[test]
+    fn full_range() {
+        let rect = Rect::try_new(Pos::new(i32::MIN, i32::MIN), Pos::new(i32::MAX, 0)).unwrap();
+        assert_eq!(rect.size(), Size::new(u32::MAX, 1 << 31));
+        assert_eq!(rect.area(), u64::from(u32::MAX) << 31);
+        assert_eq!(
+            rect.grid(1, 2).map(|cell| cell.area()).sum::<u64>(),
+            rect.area()
+        );
+    }
+
+    fn coordinate() -> impl Strategy<Value = i32> {
+        prop_oneof![i32::MIN..=i32::MIN + 64, -64..=64, i32::MAX - 64..=i32::MAX]
+    }
+
+    fn length() -> impl Strategy<Value = u32> {
+        let i32_max = i32::MAX as u32;
+        prop_oneof![
+            0..=64u32,
+            i32_max - 64..=i32_max + 64,
+            u32::MAX - 64..=u32::MAX
+        ]
+    }
+
+    fn rect() -> impl Strategy<Value = Rect> {
+        (coordinate(), coordinate(), coordinate(), coordinate()).prop_map(|(x0, y0, x1, y1)| {
+            Rect::try_new(
+                Pos::new(x0.min(x1), y0.min(y1)),
+                Pos::new(x0.max(x1), y0.max(y1)),
+            )
+            .unwrap()
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn new_size_near_bounds(x in coordinate(), y in coordinate(), width in length(), height in length()) {
+            let size = Size::new(width, height);
+            let result = Rect::try_new_size(Pos::new(x, y), size);
+            let end = |value: i32, length: u32| i32::try_from(i64::from(value) + i64::from(length));
+            if i32::try_from(width).is_err() || i32::try_from(height).is_err() {
+                prop_assert_eq!(result, Err(Error::TryFromIntError(size)));
+            } else if let (Ok(right), Ok(bottom)) = (end(x, width), end(y, height)) {
+                let rect = result.unwrap();
+                prop_assert_eq!(rect.bottom_right(), Pos::new(right, bottom));
+                prop_assert_eq!(rect.size(), size);
+                prop_assert_eq!(rect.area(), u64::from(width) * u64::from(height));
+            } else {
+                let dimension = if end(x, width).is_err() { Dimension::X } else { Dimension::Y };
+                prop_assert!(matches!(result, Err(Error::CoordinateOverflow(d, _)) if d == dimension));
+            }
+        }
+
+        #[test]
+        fn translate_near_bounds(rect in rect(), x in coordinate(), y in coordinate()) {
+            let fits = |value: i32, offset: i32| value.checked_add(offset).is_some();
+            let expected_ok = fits(rect.left(), x)
+                && fits(rect.right(), x)
+                && fits(rect.top(), y)
+                && fits(rect.bottom(), y);
+            match rect.try_translate(Pos::new(x, y)) {
+                Ok(moved) => {
+                    prop_assert!(expected_ok);
+                    prop_assert_eq!(moved.size(), rect.size());
+                    prop_assert_eq!(moved.top_left(), rect.top_left() + Pos::new(x, y));
+                }
+                Err(err) => {
+                    prop_assert!(!expected_ok);
+                    prop_assert!(matches!(err, Error::CoordinateOverflow(..)));
+                }
+            }
+        }
+
+        #[test]
+        fn accessors_near_bounds(rect in rect(), amount in length(), columns in 1..5u32, rows in 1..5u32) {
+            let width = i64::from(rect.right()) - i64::from(rect.left());
+            let height = i64::from(rect.bottom()) - i64::from(rect.top());
+            prop_assert_eq!(i64::from(rect.width()), width);
+            prop_assert_eq!(i64::from(rect.height()), height);
+            prop_assert_eq!(rect.area(), width as u64 * height as u64);
+            if !rect.is_zero_size() {
+                prop_assert!(rect.contains(rect.center()));
+            }
+            prop_assert!(rect.contains_rect(&rect.inset(amount)));
+            prop_assert!(rect.outset(amount).contains_rect(&rect));
+            let (left, right) = rect.halves_x();
+            prop_assert_eq!(left.area() + right.area(), rect.area());
+            let cells: Vec<_> = rect.grid(columns, rows).collect();
+            prop_assert!(cells.iter().all(|cell| rect.contains_rect(cell)));
+            prop_assert_eq!(cells.iter().map(Rect::area).sum::<u64>(), rect.area());
+            let parts: u64 = rect.subtract(&rect.inset(amount)).map(|part| part.area()).sum();
+            prop_assert_eq!(parts + rect.inset(amount).area(), rect.area());
+        }
+
+        #[test]
+        fn grid_line_near_bounds(a in coordinate(), b in coordinate(), count in 1..=u32::MAX, index: u32) {
+            let (low, high) = (a.min(b), a.max(b));
+            let index = index % count;
+            let line = Rect::grid_line(low, high, index, count);
+            let next = Rect::grid_line(low, high, index + 1, count);
+            prop_assert!(low <= line && line <= next && next <= high);
+            prop_assert_eq!(Rect::grid_line(low, high, count, count), high);
+        }
+    }
 }
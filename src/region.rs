@@ -0,0 +1,331 @@
+use crate::{Pos, Rect};
+use std::ops::{BitAnd, BitOr, Sub};
+use std::slice;
+
+/// A set of pixels stored as non-overlapping rects in Y-X bands, like X11 or pixman regions.
+///
+/// The rects are sorted by their top and then their left side. Rects of a band share their top
+/// and bottom and never touch each other, and vertically adjacent bands with the same columns are
+/// merged, so equal pixel sets have equal rects.
+#[derive(Eq, PartialEq, Debug, Clone, Default)]
+pub struct Region {
+    rects: Vec<Rect>,
+}
+
+impl Region {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.rects.is_empty()
+    }
+
+    #[inline]
+    pub fn rects(&self) -> &[Rect] {
+        &self.rects
+    }
+
+    #[inline]
+    pub fn iter(&self) -> slice::Iter<'_, Rect> {
+        self.rects.iter()
+    }
+
+    /// The smallest rect containing the region, zero size if it is empty.
+    #[inline]
+    pub fn bounds(&self) -> Rect {
+        self.rects
+            .iter()
+            .fold(Rect::default(), |bounds, rect| &bounds | rect)
+    }
+
+    #[inline]
+    pub fn area(&self) -> u64 {
+        self.rects.iter().map(Rect::area).sum()
+    }
+
+    #[inline]
+    pub fn contains(&self, pos: Pos) -> bool {
+        self.band_at(pos.y).iter().any(|rect| rect.contains(pos))
+    }
+
+    #[inline]
+    pub fn union(&self, other: &Region) -> Region {
+        self.combine(other, |a, b| a || b)
+    }
+
+    #[inline]
+    pub fn intersection(&self, other: &Region) -> Region {
+        self.combine(other, |a, b| a && b)
+    }
+
+    #[inline]
+    pub fn difference(&self, other: &Region) -> Region {
+        self.combine(other, |a, b| a && !b)
+    }
+
+    /// The rects of the band covering row `y`, empty if there is none.
+    fn band_at(&self, y: i32) -> &[Rect] {
+        let end = self.rects.partition_point(|rect| rect.top() <= y);
+        let Some(top) = end.checked_sub(1).map(|last| self.rects[last].top()) else {
+            return &[];
+        };
+        let start = self.rects[..end].partition_point(|rect| rect.top() < top);
+        let band = &self.rects[start..end];
+        if band[0].bottom() > y {
+            band
+        } else {
+            &[]
+        }
+    }
+
+    /// Sweeps over the rows between all band edges of both regions and keeps the columns where
+    /// `op` holds for the coverage by `self` and `other`.
+    fn combine(&self, other: &Region, op: impl Fn(bool, bool) -> bool) -> Region {
+        let mut edges: Vec<i32> = self
+            .rects
+            .iter()
+            .chain(&other.rects)
+            .flat_map(|rect| [rect.top(), rect.bottom()])
+            .collect();
+        edges.sort_unstable();
+        edges.dedup();
+
+        let mut region = Region::new();
+        for rows in edges.windows(2) {
+            let spans = |region: &Region| -> Vec<(i32, i32)> {
+                region
+                    .band_at(rows[0])
+                    .iter()
+                    .map(|rect| (rect.left(), rect.right()))
+                    .collect()
+            };
+            let spans = combine_spans(&spans(self), &spans(other), &op);
+            region.push_band(rows[0], rows[1], &spans);
+        }
+        region
+    }
+
+    /// Appends a band below all others, merging it into the last band if that one ends at `top`
+    /// and has the same columns.
+    fn push_band(&mut self, top: i32, bottom: i32, spans: &[(i32, i32)]) {
+        if spans.is_empty() {
+            return;
+        }
+        let last_band = self
+            .rects
+            .last()
+            .map_or(&[][..], |last| self.band_at(last.top()));
+        let start = self.rects.len() - last_band.len();
+        let same_columns = last_band.len() == spans.len()
+            && last_band
+                .iter()
+                .zip(spans)
+                .all(|(rect, &(left, right))| (rect.left(), rect.right()) == (left, right));
+        if same_columns && last_band[0].bottom() == top {
+            for rect in &mut self.rects[start..] {
+                *rect = new_rect(rect.left(), rect.top(), rect.right(), bottom);
+            }
+        } else {
+            self.rects.extend(
+                spans
+                    .iter()
+                    .map(|&(left, right)| new_rect(left, top, right, bottom)),
+            );
+        }
+    }
+}
+
+#[inline]
+fn new_rect(left: i32, top: i32, right: i32, bottom: i32) -> Rect {
+    Rect::try_new(Pos::new(left, top), Pos::new(right, bottom))
+        .unwrap_or_else(|_| unreachable!(/*bands and spans are ordered*/))
+}
+
+/// Combines sorted, disjoint column spans, joining spans that touch.
+fn combine_spans(
+    a: &[(i32, i32)],
+    b: &[(i32, i32)],
+    op: impl Fn(bool, bool) -> bool,
+) -> Vec<(i32, i32)> {
+    let covered = |spans: &[(i32, i32)], x: i32| {
+        let index = spans.partition_point(|&(_, right)| right <= x);
+        spans.get(index).is_some_and(|&(left, _)| left <= x)
+    };
+    let mut edges: Vec<i32> = a
+        .iter()
+        .chain(b)
+        .flat_map(|&(left, right)| [left, right])
+        .collect();
+    edges.sort_unstable();
+    edges.dedup();
+
+    let mut spans: Vec<(i32, i32)> = vec![];
+    for columns in edges.windows(2) {
+        let (left, right) = (columns[0], columns[1]);
+        if !op(covered(a, left), covered(b, left)) {
+            continue;
+        }
+        match spans.last_mut() {
+            Some(last) if last.1 == left => last.1 = right,
+            _ => spans.push((left, right)),
+        }
+    }
+    spans
+}
+
+impl From<Rect> for Region {
+    #[inline]
+    fn from(rect: Rect) -> Self {
+        Self {
+            rects: if rect.is_zero_size() {
+                vec![]
+            } else {
+                vec![rect]
+            },
+        }
+    }
+}
+
+impl FromIterator<Rect> for Region {
+    /// The union of all rects.
+    fn from_iter<T: IntoIterator<Item = Rect>>(rects: T) -> Self {
+        rects
+            .into_iter()
+            .fold(Region::new(), |region, rect| region.union(&rect.into()))
+    }
+}
+
+impl<'a> IntoIterator for &'a Region {
+    type Item = &'a Rect;
+    type IntoIter = slice::Iter<'a, Rect>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl BitOr<&Region> for &Region {
+    type Output = Region;
+
+    #[inline]
+    fn bitor(self, rhs: &Region) -> Self::Output {
+        self.union(rhs)
+    }
+}
+
+impl BitAnd<&Region> for &Region {
+    type Output = Region;
+
+    #[inline]
+    fn bitand(self, rhs: &Region) -> Self::Output {
+        self.intersection(rhs)
+    }
+}
+
+impl Sub<&Region> for &Region {
+    type Output = Region;
+
+    #[inline]
+    fn sub(self, rhs: &Region) -> Self::Output {
+        self.difference(rhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Size;
+    use proptest::prelude::*;
+
+    fn rect(left: i32, top: i32, right: i32, bottom: i32) -> Rect {
+        Rect::try_new(Pos::new(left, top), Pos::new(right, bottom)).unwrap()
+    }
+
+    fn region() -> impl Strategy<Value = Region> {
+        prop::collection::vec((0..12, 0..12, 0..8u32, 0..8u32), 0..6).prop_map(|rects| {
+            rects
+                .into_iter()
+                .map(|(x, y, width, height)| {
+                    Rect::try_new_size(Pos::new(x, y), Size::new(width, height)).unwrap()
+                })
+                .collect()
+        })
+    }
+
+    fn pixels(region: &Region) -> Vec<bool> {
+        (0..20)
+            .flat_map(|y| (0..20).map(move |x| Pos::new(x, y)))
+            .map(|pos| region.iter().any(|rect| rect.contains(pos)))
+            .collect()
+    }
+
+    /// Checks the banded form documented on [`Region`].
+    fn assert_normalized(region: &Region) {
+        for pair in region.rects().windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            if a.top() == b.top() {
+                assert_eq!(a.bottom(), b.bottom());
+                assert!(a.right() < b.left());
+            } else {
+                assert!(a.bottom() <= b.top());
+            }
+        }
+        assert!(region.iter().all(|rect| !rect.is_zero_size()));
+    }
+
+    proptest! {
+        #[test]
+        fn operations_match_pixels(a in region(), b in region()) {
+            let (pixels_a, pixels_b) = (pixels(&a), pixels(&b));
+            let ops: [fn(bool, bool) -> bool; 3] = [|a, b| a || b, |a, b| a && b, |a, b| a && !b];
+            for (region, op) in [&a | &b, &a & &b, &a - &b].into_iter().zip(ops) {
+                assert_normalized(&region);
+                let expected: Vec<_> =
+                    pixels_a.iter().zip(&pixels_b).map(|(&a, &b)| op(a, b)).collect();
+                prop_assert_eq!(pixels(&region), expected);
+                let covered = pixels(&region).iter().filter(|&&set| set).count();
+                prop_assert_eq!(region.area(), covered as u64);
+            }
+        }
+
+        #[test]
+        fn equal_pixels_give_equal_regions(a in region(), b in region()) {
+            prop_assert_eq!(&(&a | &b) - &b, &a - &b);
+            prop_assert_eq!(&a | &b, &b | &a);
+        }
+    }
+
+    #[test]
+    fn from_rects() {
+        let region: Region = [rect(0, 0, 4, 2), rect(2, 1, 6, 3)].into_iter().collect();
+        assert_eq!(
+            region.rects(),
+            [rect(0, 0, 4, 1), rect(0, 1, 6, 2), rect(2, 2, 6, 3)]
+        );
+        assert_eq!(region.bounds(), rect(0, 0, 6, 3));
+        assert!(region.contains(Pos::new(5, 1)));
+        assert!(!region.contains(Pos::new(1, 2)));
+        assert_eq!(Region::from(rect(1, 1, 1, 5)), Region::new());
+    }
+
+    #[test]
+    fn difference_with_hole() {
+        let region = &Region::from(rect(0, 0, 4, 4)) - &Region::from(rect(1, 1, 3, 3));
+        assert_eq!(
+            region.iter().copied().collect::<Vec<_>>(),
+            [
+                rect(0, 0, 4, 1),
+                rect(0, 1, 1, 3),
+                rect(3, 1, 4, 3),
+                rect(0, 3, 4, 4)
+            ]
+        );
+        let filled = &region | &Region::from(rect(1, 1, 3, 3));
+        assert_eq!(filled, Region::from(rect(0, 0, 4, 4)));
+        assert!((&region & &Region::from(rect(1, 1, 3, 3))).is_empty());
+    }
+}
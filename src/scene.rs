@@ -0,0 +1,21 @@
+use crate::colors::{GREEN, RED};
+use crate::{Draw, Pos};
+
+/// Draws the demo scene over the whole canvas.
+pub fn draw(canvas: &mut impl Draw) {
+    let size = canvas.size();
+    let (width, height) = (size.width as i32, size.height as i32);
+
+    canvas.draw_circle(Pos::new(0, 0), 1, RED, GREEN);
+    canvas.draw_circle(Pos::new(width - 1, 0), 1, RED, GREEN);
+    canvas.draw_circle(Pos::new(0, height - 1), 1, RED, GREEN);
+    canvas.draw_circle(Pos::new(width - 1, height - 1), 1, RED, GREEN);
+    canvas.draw_line(Pos::new(0, 0), Pos::new(width, height), RED);
+    canvas.draw_line(Pos::new(width + 10, height), Pos::new(10, 0), RED);
+    canvas.draw_line(Pos::new(width, 0), Pos::new(0, height), RED);
+    canvas.draw_line(Pos::new(10, height), Pos::new(width + 10, 0), RED);
+    canvas.draw_line(Pos::new(100, 100), Pos::new(100, 200), GREEN);
+    canvas.draw_line(Pos::new(100, 100), Pos::new(200, 100), GREEN);
+    canvas.draw_line(Pos::new(200, 200), Pos::new(100, 200), GREEN);
+    canvas.draw_line(Pos::new(200, 200), Pos::new(200, 100), GREEN);
+}
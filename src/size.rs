@@ -1,6 +1,11 @@
-use glam::UVec2;
+use glam::{UVec2, Vec2};
 use std::fmt::{Display, Formatter};
+use std::ops::Mul;
 
+/// A width and height in pixels.
+///
+/// Scaling behaves like `u32` multiplication: it panics on overflow in debug builds and wraps in
+/// release builds. The `checked_*` and `saturating_*` methods never panic.
 #[derive(Eq, PartialEq, Debug, Copy, Clone, Default)]
 pub struct Size {
     pub width: u32,
@@ -12,6 +17,50 @@ impl Size {
     pub fn new(width: u32, height: u32) -> Self {
         Self { width, height }
     }
+
+    #[inline]
+    pub fn min(self, other: Size) -> Size {
+        Size::new(self.width.min(other.width), self.height.min(other.height))
+    }
+
+    #[inline]
+    pub fn max(self, other: Size) -> Size {
+        Size::new(self.width.max(other.width), self.height.max(other.height))
+    }
+
+    /// Clamps both dimensions, panics if `min` is greater than `max` in either of them.
+    #[inline]
+    pub fn clamp(self, min: Size, max: Size) -> Size {
+        Size::new(
+            self.width.clamp(min.width, max.width),
+            self.height.clamp(min.height, max.height),
+        )
+    }
+
+    #[inline]
+    pub fn checked_mul(self, factor: u32) -> Option<Size> {
+        Some(Size::new(
+            self.width.checked_mul(factor)?,
+            self.height.checked_mul(factor)?,
+        ))
+    }
+
+    #[inline]
+    pub fn saturating_mul(self, factor: u32) -> Size {
+        Size::new(
+            self.width.saturating_mul(factor),
+            self.height.saturating_mul(factor),
+        )
+    }
+}
+
+impl Mul<u32> for Size {
+    type Output = Size;
+
+    #[inline]
+    fn mul(self, rhs: u32) -> Self::Output {
+        Size::new(self.width * rhs, self.height * rhs)
+    }
 }
 
 impl From<Size> for UVec2 {
@@ -28,6 +77,36 @@ impl From<UVec2> for Size {
     }
 }
 
+impl From<(u32, u32)> for Size {
+    #[inline]
+    fn from((width, height): (u32, u32)) -> Self {
+        Self::new(width, height)
+    }
+}
+
+impl From<Size> for (u32, u32) {
+    #[inline]
+    fn from(size: Size) -> Self {
+        (size.width, size.height)
+    }
+}
+
+/// Dimensions beyond 2²⁴ are rounded to the nearest `f32`.
+impl From<Size> for Vec2 {
+    #[inline]
+    fn from(size: Size) -> Self {
+        Self::new(size.width as f32, size.height as f32)
+    }
+}
+
+/// Rounds up to whole pixels, saturating at 0 and `u32::MAX`. NaN maps to 0.
+impl From<Vec2> for Size {
+    #[inline]
+    fn from(vec2: Vec2) -> Self {
+        Self::new(vec2.x.ceil() as u32, vec2.y.ceil() as u32)
+    }
+}
+
 impl Display for Size {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "Size: width = {}, height = {}", self.width, self.height)
@@ -58,4 +137,49 @@ mod tests {
     fn size_from_ivec2() {
         assert_eq!(Size::new(1, 2), UVec2::new(1, 2).into());
     }
+
+    #[test]
+    fn tuples() {
+        assert_eq!(Size::from((1, 2)), Size::new(1, 2));
+        assert_eq!(<(u32, u32)>::from(Size::new(1, 2)), (1, 2));
+    }
+
+    #[test]
+    fn vec2() {
+        assert_eq!(Vec2::from(Size::new(1, 2)), Vec2::new(1.0, 2.0));
+        assert_eq!(Size::from(Vec2::new(1.5, 2.0)), Size::new(2, 2));
+        assert_eq!(
+            Size::from(Vec2::new(-3.0, f32::INFINITY)),
+            Size::new(0, u32::MAX)
+        );
+        assert_eq!(Size::from(Vec2::new(f32::NAN, 0.0)), Size::new(0, 0));
+    }
+
+    #[test]
+    fn mul() {
+        assert_eq!(Size::new(2, 3) * 4, Size::new(8, 12));
+        assert_eq!(Size::new(2, 3).checked_mul(4), Some(Size::new(8, 12)));
+        assert_eq!(Size::new(1, u32::MAX / 2 + 1).checked_mul(2), None);
+        assert_eq!(
+            Size::new(1, u32::MAX / 2 + 1).saturating_mul(2),
+            Size::new(2, u32::MAX)
+        );
+    }
+
+    #[test]
+    #[cfg_attr(debug_assertions, should_panic(expected = "overflow"))]
+    fn mul_overflow() {
+        assert_eq!(Size::new(0, u32::MAX / 2 + 1) * 2, Size::new(0, 0));
+    }
+
+    #[test]
+    fn min_max_clamp() {
+        let (a, b) = (Size::new(1, 5), Size::new(3, 2));
+        assert_eq!(a.min(b), Size::new(1, 2));
+        assert_eq!(a.max(b), Size::new(3, 5));
+        assert_eq!(
+            Size::new(0, 9).clamp(Size::new(1, 1), Size::new(5, 5)),
+            Size::new(1, 5)
+        );
+    }
 }
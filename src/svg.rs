@@ -0,0 +1,341 @@
+use crate::{Draw, Error, Pos, Rect, Size};
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Records drawing calls as SVG elements instead of rasterizing them.
+///
+/// Shapes are placed so that they cover the same pixels as on a [`Canvas`](crate::Canvas):
+/// pixel `(x, y)` is the unit square at `(x, y)`, lines and circles go through pixel centers.
+/// Clip rects become shared `clipPath`s.
+pub struct SvgRecorder {
+    global_rect: Rect,
+    background: u32,
+    clips: Vec<Rect>,
+    elements: Vec<Element>,
+}
+
+#[derive(Debug, Copy, Clone)]
+enum Shape {
+    Dot {
+        pos: Pos,
+        color: u32,
+    },
+    Line {
+        from: Pos,
+        to: Pos,
+        color: u32,
+    },
+    Circle {
+        center: Pos,
+        radius: u32,
+        outline_color: u32,
+        fill_color: u32,
+    },
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Element {
+    shape: Shape,
+    clip: Option<usize>,
+}
+
+struct Color(u32);
+
+impl Display for Color {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{:06x}", self.0 & 0xFF_FFFF)
+    }
+}
+
+/// Center of a pixel row or column.
+struct Center(i32);
+
+impl Display for Center {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", f64::from(self.0) + 0.5)
+    }
+}
+
+impl SvgRecorder {
+    /// Panics if a dimension exceeds `i32::MAX`.
+    #[inline]
+    pub fn new(background: u32, size: Size) -> Self {
+        Self {
+            global_rect: Rect::try_new_size(Pos::new(0, 0), size)
+                .unwrap_or_else(|err| panic!("invalid SVG size: {err}")),
+            background,
+            clips: vec![],
+            elements: vec![],
+        }
+    }
+
+    /// Drops every recorded element and restarts from a plain `color` background.
+    #[inline]
+    pub fn clear(&mut self, color: u32) {
+        self.background = color;
+        self.clips.clear();
+        self.elements.clear();
+    }
+
+    pub fn write_svg(&self, mut writer: impl Write) -> Result<(), Error> {
+        let Size { width, height } = self.global_rect.size();
+        writeln!(
+            writer,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+        )?;
+        if !self.clips.is_empty() {
+            writeln!(writer, "<defs>")?;
+            for (id, clip) in self.clips.iter().enumerate() {
+                writeln!(
+                    writer,
+                    r#"<clipPath id="clip{id}"><rect x="{}" y="{}" width="{}" height="{}"/></clipPath>"#,
+                    clip.left(),
+                    clip.top(),
+                    clip.width(),
+                    clip.height()
+                )?;
+            }
+            writeln!(writer, "</defs>")?;
+        }
+        writeln!(
+            writer,
+            r#"<rect width="{width}" height="{height}" fill="{}"/>"#,
+            Color(self.background)
+        )?;
+        for element in &self.elements {
+            match element.shape {
+                Shape::Dot { pos, color } => write!(
+                    writer,
+                    r#"<rect x="{}" y="{}" width="1" height="1" fill="{}""#,
+                    pos.x,
+                    pos.y,
+                    Color(color)
+                )?,
+                Shape::Line { from, to, color } => write!(
+                    writer,
+                    r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" stroke-linecap="square""#,
+                    Center(from.x),
+                    Center(from.y),
+                    Center(to.x),
+                    Center(to.y),
+                    Color(color)
+                )?,
+                Shape::Circle {
+                    center,
+                    radius,
+                    outline_color,
+                    fill_color,
+                } => write!(
+                    writer,
+                    r#"<circle cx="{}" cy="{}" r="{radius}" fill="{}" stroke="{}""#,
+                    Center(center.x),
+                    Center(center.y),
+                    Color(fill_color),
+                    Color(outline_color)
+                )?,
+            }
+            if let Some(clip) = element.clip {
+                write!(writer, r#" clip-path="url(#clip{clip})""#)?;
+            }
+            writeln!(writer, "/>")?;
+        }
+        writeln!(writer, "</svg>")?;
+        Ok(())
+    }
+
+    #[inline]
+    pub fn save_svg(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_svg(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    fn push(&mut self, shape: Shape, rect: Option<&Rect>) {
+        let shape = match shape {
+            Shape::Circle {
+                center,
+                radius: 0,
+                outline_color,
+                ..
+            } => Shape::Dot {
+                pos: center,
+                color: outline_color,
+            },
+            Shape::Line { from, to, color } if from == to => Shape::Dot { pos: from, color },
+            shape => shape,
+        };
+        let clip = match rect {
+            None => None,
+            Some(rect) => {
+                let Some(rect) = (&self.global_rect & rect).filter(|rect| !rect.is_zero_size())
+                else {
+                    return;
+                };
+                if rect == self.global_rect {
+                    None
+                } else if let Some(clip) = self.clips.iter().position(|clip| *clip == rect) {
+                    Some(clip)
+                } else {
+                    self.clips.push(rect);
+                    Some(self.clips.len() - 1)
+                }
+            }
+        };
+        self.elements.push(Element { shape, clip });
+    }
+}
+
+impl Draw for SvgRecorder {
+    #[inline]
+    fn size(&self) -> Size {
+        self.global_rect.size()
+    }
+
+    #[inline]
+    fn draw_circle_in_rect(
+        &mut self,
+        center: Pos,
+        radius: u32,
+        outline_color: u32,
+        fill_color: u32,
+        rect: &Rect,
+    ) {
+        self.push(
+            Shape::Circle {
+                center,
+                radius,
+                outline_color,
+                fill_color,
+            },
+            Some(rect),
+        );
+    }
+
+    #[inline]
+    fn draw_dot_in_rect(&mut self, pos: Pos, color: u32, rect: &Rect) {
+        self.push(Shape::Dot { pos, color }, Some(rect));
+    }
+
+    #[inline]
+    fn draw_line_in_rect(&mut self, from: Pos, to: Pos, color: u32, rect: &Rect) {
+        self.push(Shape::Line { from, to, color }, Some(rect));
+    }
+
+    #[inline]
+    fn draw_circle(&mut self, center: Pos, radius: u32, outline_color: u32, fill_color: u32) {
+        self.push(
+            Shape::Circle {
+                center,
+                radius,
+                outline_color,
+                fill_color,
+            },
+            None,
+        );
+    }
+
+    #[inline]
+    fn draw_dot(&mut self, pos: Pos, color: u32) {
+        self.push(Shape::Dot { pos, color }, None);
+    }
+
+    #[inline]
+    fn draw_line(&mut self, from: Pos, to: Pos, color: u32) {
+        self.push(Shape::Line { from, to, color }, None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colors::{GRAY, GREEN, RED};
+
+    fn svg(recorder: &SvgRecorder) -> String {
+        let mut data = vec![];
+        recorder.write_svg(&mut data).unwrap();
+        String::from_utf8(data).unwrap()
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(
+            svg(&SvgRecorder::new(GRAY, Size::new(3, 2))),
+            r##"<svg xmlns="http://www.w3.org/2000/svg" width="3" height="2" viewBox="0 0 3 2">
+<rect width="3" height="2" fill="#808080"/>
+</svg>
+"##
+        );
+    }
+
+    #[test]
+    fn shapes() {
+        let mut recorder = SvgRecorder::new(GRAY, Size::new(10, 10));
+        recorder.draw_dot(Pos::new(1, 2), RED);
+        recorder.draw_line(Pos::new(0, 0), Pos::new(9, 4), GREEN);
+        recorder.draw_circle(Pos::new(5, 5), 3, RED, GREEN);
+        recorder.draw_circle(Pos::new(7, 7), 0, RED, GREEN);
+        recorder.draw_line(Pos::new(8, 8), Pos::new(8, 8), GREEN);
+        assert_eq!(
+            svg(&recorder),
+            r##"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10" viewBox="0 0 10 10">
+<rect width="10" height="10" fill="#808080"/>
+<rect x="1" y="2" width="1" height="1" fill="#ff0000"/>
+<line x1="0.5" y1="0.5" x2="9.5" y2="4.5" stroke="#00ff00" stroke-linecap="square"/>
+<circle cx="5.5" cy="5.5" r="3" fill="#00ff00" stroke="#ff0000"/>
+<rect x="7" y="7" width="1" height="1" fill="#ff0000"/>
+<rect x="8" y="8" width="1" height="1" fill="#00ff00"/>
+</svg>
+"##
+        );
+    }
+
+    #[test]
+    fn clip_paths() {
+        let mut recorder = SvgRecorder::new(GRAY, Size::new(10, 10));
+        let rect = Rect::try_new(Pos::new(2, 2), Pos::new(20, 5)).unwrap();
+        recorder.draw_dot_in_rect(Pos::new(3, 3), RED, &rect);
+        recorder.draw_line_in_rect(Pos::new(-5, -5), Pos::new(15, 15), RED, &rect);
+        recorder.draw_circle_in_rect(
+            Pos::new(1, 1),
+            2,
+            RED,
+            GREEN,
+            &Rect::try_new(Pos::new(-1, -1), Pos::new(11, 11)).unwrap(),
+        );
+        recorder.draw_dot_in_rect(
+            Pos::new(3, 3),
+            RED,
+            &Rect::try_new(Pos::new(20, 20), Pos::new(30, 30)).unwrap(),
+        );
+        assert_eq!(
+            svg(&recorder),
+            r##"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10" viewBox="0 0 10 10">
+<defs>
+<clipPath id="clip0"><rect x="2" y="2" width="8" height="3"/></clipPath>
+</defs>
+<rect width="10" height="10" fill="#808080"/>
+<rect x="3" y="3" width="1" height="1" fill="#ff0000" clip-path="url(#clip0)"/>
+<line x1="-4.5" y1="-4.5" x2="15.5" y2="15.5" stroke="#ff0000" stroke-linecap="square" clip-path="url(#clip0)"/>
+<circle cx="1.5" cy="1.5" r="2" fill="#00ff00" stroke="#ff0000"/>
+</svg>
+"##
+        );
+    }
+
+    #[test]
+    fn clear() {
+        let mut recorder = SvgRecorder::new(GRAY, Size::new(1, 1));
+        recorder.draw_dot(Pos::new(0, 0), RED);
+        recorder.clear(GREEN);
+        assert_eq!(
+            svg(&recorder),
+            r##"<svg xmlns="http://www.w3.org/2000/svg" width="1" height="1" viewBox="0 0 1 1">
+<rect width="1" height="1" fill="#00ff00"/>
+</svg>
+"##
+        );
+    }
+}
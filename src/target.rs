@@ -0,0 +1,176 @@
+#[cfg(feature = "window")]
+mod softbuffer;
+
+#[cfg(feature = "window")]
+pub use self::softbuffer::SoftbufferTarget;
+
+use crate::{Canvas, Error};
+use std::path::{Path, PathBuf};
+
+/// Destination for rendered frames: a window, a file, a video stream or plain memory.
+///
+/// Drawing happens on a [`Canvas`], which is handed to the target once per frame.
+pub trait RenderTarget {
+    /// Shows or stores `canvas` as the next frame.
+    fn present(&mut self, canvas: &Canvas) -> Result<(), Error>;
+
+    /// Completes the output after the last frame, e.g. writes a trailer or flushes buffers.
+    ///
+    /// Presenting again afterwards starts a new output where the format allows it.
+    #[inline]
+    fn close(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<T: RenderTarget + ?Sized> RenderTarget for &mut T {
+    #[inline]
+    fn present(&mut self, canvas: &Canvas) -> Result<(), Error> {
+        (**self).present(canvas)
+    }
+
+    #[inline]
+    fn close(&mut self) -> Result<(), Error> {
+        (**self).close()
+    }
+}
+
+impl<T: RenderTarget + ?Sized> RenderTarget for Box<T> {
+    #[inline]
+    fn present(&mut self, canvas: &Canvas) -> Result<(), Error> {
+        (**self).present(canvas)
+    }
+
+    #[inline]
+    fn close(&mut self) -> Result<(), Error> {
+        (**self).close()
+    }
+}
+
+/// Keeps a copy of the last presented frame in memory.
+#[derive(Default)]
+pub struct ImageTarget {
+    image: Option<Canvas>,
+    frames: u64,
+}
+
+impl ImageTarget {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The last presented frame.
+    #[inline]
+    pub fn image(&self) -> Option<&Canvas> {
+        self.image.as_ref()
+    }
+
+    /// Number of frames presented so far.
+    #[inline]
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    #[inline]
+    pub fn into_image(self) -> Option<Canvas> {
+        self.image
+    }
+}
+
+impl RenderTarget for ImageTarget {
+    fn present(&mut self, canvas: &Canvas) -> Result<(), Error> {
+        self.image = Some(Canvas::from_pixels(canvas.pixels().clone()));
+        self.frames += 1;
+        Ok(())
+    }
+}
+
+/// Saves frames as image files with [`Canvas::save`], the format is taken from the extension.
+///
+/// A `{}` in the file name is replaced with the zero padded frame number so that every frame gets
+/// its own file; otherwise each frame overwrites the previous one.
+pub struct FileTarget {
+    path: PathBuf,
+    frame: u64,
+}
+
+impl FileTarget {
+    #[inline]
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            frame: 0,
+        }
+    }
+
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Path the given frame is saved to.
+    pub fn frame_path(&self, frame: u64) -> PathBuf {
+        match self.path.to_str() {
+            Some(path) if path.contains("{}") => path.replace("{}", &format!("{frame:06}")).into(),
+            _ => self.path.clone(),
+        }
+    }
+}
+
+impl RenderTarget for FileTarget {
+    fn present(&mut self, canvas: &Canvas) -> Result<(), Error> {
+        canvas.save(self.frame_path(self.frame))?;
+        self.frame += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colors::{GRAY, RED};
+    use crate::{Pos, Size};
+
+    #[test]
+    fn image_target() {
+        let mut target = ImageTarget::new();
+        assert!(target.image().is_none());
+        let mut canvas = Canvas::new(GRAY, Size::new(3, 2));
+        target.present(&canvas).unwrap();
+        canvas.draw_dot(Pos::new(1, 1), RED);
+        target.present(&canvas).unwrap();
+        assert_eq!(target.frames(), 2);
+        assert_eq!(target.image().unwrap().pixels(), canvas.pixels());
+        target.present(&Canvas::new(RED, Size::new(1, 1))).unwrap();
+        assert_eq!(target.into_image().unwrap().size(), Size::new(1, 1));
+    }
+
+    #[test]
+    fn frame_path() {
+        assert_eq!(
+            FileTarget::new("out/frame-{}.qoi").frame_path(12),
+            Path::new("out/frame-000012.qoi")
+        );
+        assert_eq!(
+            FileTarget::new("out/frame.qoi").frame_path(12),
+            Path::new("out/frame.qoi")
+        );
+    }
+
+    #[test]
+    fn dyn_targets() {
+        let mut first = ImageTarget::new();
+        let mut second = ImageTarget::new();
+        {
+            let mut targets: Vec<Box<dyn RenderTarget + '_>> =
+                vec![Box::new(&mut first), Box::new(&mut second)];
+            let canvas = Canvas::new(GRAY, Size::new(1, 1));
+            for target in &mut targets {
+                target.present(&canvas).unwrap();
+                target.close().unwrap();
+            }
+        }
+        assert_eq!((first.frames(), second.frames()), (1, 1));
+    }
+}
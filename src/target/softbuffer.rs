@@ -0,0 +1,83 @@
+use crate::canvas;
+use crate::target::RenderTarget;
+use crate::{Canvas, Error, Rect, Size};
+use softbuffer::{Context, SoftBufferError, Surface};
+use std::num::NonZeroU32;
+use winit::raw_window_handle::{HasDisplayHandle, HasWindowHandle};
+
+/// Presents frames in a window through a softbuffer surface.
+///
+/// The surface is resized to the canvas on every frame, so the canvas size decides what is shown.
+/// Only the [damage](Canvas::damage) of the canvas is presented, unless the size changed, and
+/// frames without damage are skipped; clear the damage after presenting.
+pub struct SoftbufferTarget<D, W> {
+    surface: Surface<D, W>,
+    size: Option<Size>,
+}
+
+impl<D: HasDisplayHandle, W: HasWindowHandle> SoftbufferTarget<D, W> {
+    pub fn new(display: D, window: W) -> Result<Self, Error> {
+        let context = Context::new(display).map_err(softbuffer_error)?;
+        let surface = Surface::new(&context, window).map_err(softbuffer_error)?;
+        Ok(Self {
+            surface,
+            size: None,
+        })
+    }
+}
+
+impl<D: HasDisplayHandle, W: HasWindowHandle> RenderTarget for SoftbufferTarget<D, W> {
+    fn present(&mut self, canvas: &Canvas) -> Result<(), Error> {
+        let size = canvas.size();
+        let (Some(width), Some(height)) =
+            (NonZeroU32::new(size.width), NonZeroU32::new(size.height))
+        else {
+            return Ok(());
+        };
+        let resized = self.size != Some(size);
+        if resized {
+            self.surface
+                .resize(width, height)
+                .map_err(softbuffer_error)?;
+            self.size = Some(size);
+        } else if canvas.damage().is_empty() {
+            return Ok(());
+        }
+        // the buffer may hold an older frame, so it is filled completely
+        let mut buffer = self.surface.buffer_mut().map_err(softbuffer_error)?;
+        buffer.copy_from_slice(
+            canvas
+                .pixels()
+                .as_slice()
+                .ok_or(canvas::Error::InvalidBufferLayout)?,
+        );
+        if resized {
+            buffer.present()
+        } else {
+            buffer.present_with_damage(
+                &canvas
+                    .damage()
+                    .iter()
+                    .filter_map(damage)
+                    .collect::<Vec<_>>(),
+            )
+        }
+        .map_err(softbuffer_error)
+    }
+}
+
+#[inline]
+fn damage(rect: &Rect) -> Option<softbuffer::Rect> {
+    Some(softbuffer::Rect {
+        x: u32::try_from(rect.left()).ok()?,
+        y: u32::try_from(rect.top()).ok()?,
+        width: NonZeroU32::new(rect.size().width)?,
+        height: NonZeroU32::new(rect.size().height)?,
+    })
+}
+
+/// Softbuffer errors may carry raw window handles, which are neither `Send` nor `Sync`.
+#[inline]
+fn softbuffer_error(err: SoftBufferError) -> Error {
+    Error::Softbuffer(err.to_string())
+}
@@ -0,0 +1,197 @@
+use crate::image::{from_rgb, to_rgb};
+use crate::target::RenderTarget;
+use crate::{Canvas, Error, Size};
+use ndarray::Array2;
+use std::io::Write;
+
+const UPPER_HALF_BLOCK: char = '▀';
+
+/// Presents canvases on an ANSI truecolor terminal.
+///
+/// Every character cell shows two vertically stacked pixels via `▀`, with the foreground color
+/// as the upper and the background color as the lower pixel. The canvas is box-filtered down to
+/// fit `cells` keeping its aspect ratio, and each frame is redrawn in place.
+pub struct TerminalPresenter<W: Write> {
+    writer: W,
+    cells: Size,
+    started: bool,
+    frame: String,
+}
+
+impl<W: Write> TerminalPresenter<W> {
+    #[inline]
+    pub fn new(writer: W, cells: Size) -> Self {
+        Self {
+            writer,
+            cells,
+            started: false,
+            frame: String::new(),
+        }
+    }
+
+    pub fn present(&mut self, canvas: &Canvas) -> Result<(), Error> {
+        let pixels = downsample(canvas.pixels(), self.fit(canvas.size()));
+        self.frame.clear();
+        if !self.started {
+            // clear the screen and hide the cursor
+            self.frame.push_str("\x1b[2J\x1b[?25l");
+            self.started = true;
+        }
+        self.frame.push_str("\x1b[H");
+        let (height, width) = pixels.dim();
+        for y in (0..height).step_by(2) {
+            let mut current = None;
+            for x in 0..width {
+                let upper = pixels[(y, x)];
+                let lower = pixels.get((y + 1, x)).copied();
+                if current != Some((upper, lower)) {
+                    let [r, g, b] = to_rgb(upper);
+                    self.frame.push_str(&format!("\x1b[38;2;{r};{g};{b}m"));
+                    match lower {
+                        Some(lower) => {
+                            let [r, g, b] = to_rgb(lower);
+                            self.frame.push_str(&format!("\x1b[48;2;{r};{g};{b}m"));
+                        }
+                        None => self.frame.push_str("\x1b[49m"),
+                    }
+                    current = Some((upper, lower));
+                }
+                self.frame.push(UPPER_HALF_BLOCK);
+            }
+            self.frame.push_str("\x1b[0m\x1b[K\r\n");
+        }
+        self.writer.write_all(self.frame.as_bytes())?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// Restores the terminal attributes and cursor, returning the underlying writer.
+    pub fn finish(mut self) -> Result<W, Error> {
+        self.restore()?;
+        Ok(self.writer)
+    }
+
+    /// Restores the terminal, the next frame clears the screen again.
+    fn restore(&mut self) -> Result<(), Error> {
+        if self.started {
+            self.writer.write_all(b"\x1b[0m\x1b[?25h")?;
+            self.writer.flush()?;
+            self.started = false;
+        }
+        Ok(())
+    }
+
+    /// Largest pixel grid within the cells, two pixels per cell vertically, with the aspect ratio
+    /// of `size`.
+    fn fit(&self, size: Size) -> Size {
+        let (max_width, max_height) = (
+            u64::from(self.cells.width),
+            u64::from(self.cells.height) * 2,
+        );
+        let (width, height) = (u64::from(size.width.max(1)), u64::from(size.height.max(1)));
+        let (width, height) = if width * max_height > height * max_width {
+            (max_width, height * max_width / width)
+        } else {
+            (width * max_height / height, max_height)
+        };
+        Size::new(width.max(1) as u32, height.max(1) as u32)
+    }
+}
+
+impl<W: Write> RenderTarget for TerminalPresenter<W> {
+    #[inline]
+    fn present(&mut self, canvas: &Canvas) -> Result<(), Error> {
+        TerminalPresenter::present(self, canvas)
+    }
+
+    #[inline]
+    fn close(&mut self) -> Result<(), Error> {
+        self.restore()
+    }
+}
+
+/// Averages the source pixels covered by every target pixel, or repeats the nearest one when
+/// upscaling.
+fn downsample(pixels: &Array2<u32>, size: Size) -> Array2<u32> {
+    let (height, width) = pixels.dim();
+    if height == 0 || width == 0 {
+        return Array2::zeros((0, 0));
+    }
+    let span = |index: usize, target: u32, source: usize| {
+        let start = index * source / target as usize;
+        let end = ((index + 1) * source / target as usize).max(start + 1);
+        start..end
+    };
+    Array2::from_shape_fn((size.height as usize, size.width as usize), |(y, x)| {
+        let mut sum = [0u64; 3];
+        let mut count = 0;
+        for y in span(y, size.height, height) {
+            for x in span(x, size.width, width) {
+                for (sum, channel) in sum.iter_mut().zip(to_rgb(pixels[(y, x)])) {
+                    *sum += u64::from(channel);
+                }
+                count += 1;
+            }
+        }
+        from_rgb(sum.map(|sum| ((sum + count / 2) / count) as u8))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+
+    #[test]
+    fn downsample_average() {
+        assert_eq!(
+            downsample(
+                &array![
+                    [0x000000, 0xFEFEFE, 0xFF0000],
+                    [0x000000, 0x000000, 0xFF0000]
+                ],
+                Size::new(1, 1)
+            ),
+            array![[0x7F2A2A]]
+        );
+        assert_eq!(
+            downsample(&array![[0x000000, 0x000010]], Size::new(4, 1)),
+            array![[0x000000, 0x000000, 0x000010, 0x000010]]
+        );
+    }
+
+    #[test]
+    fn fit_keeps_aspect_ratio() {
+        let presenter = TerminalPresenter::new(vec![], Size::new(80, 24));
+        assert_eq!(presenter.fit(Size::new(640, 480)), Size::new(64, 48));
+        assert_eq!(presenter.fit(Size::new(1600, 100)), Size::new(80, 5));
+    }
+
+    #[test]
+    fn present() {
+        let mut presenter = TerminalPresenter::new(vec![], Size::new(2, 1));
+        let canvas = Canvas::from_pixels(array![[0xFF0000, 0xFF0000], [0x0000FF, 0x00FF00]]);
+        presenter.present(&canvas).unwrap();
+        presenter.present(&canvas).unwrap();
+        let frame = "\x1b[H\
+            \x1b[38;2;255;0;0m\x1b[48;2;0;0;255m▀\
+            \x1b[38;2;255;0;0m\x1b[48;2;0;255;0m▀\
+            \x1b[0m\x1b[K\r\n";
+        assert_eq!(
+            String::from_utf8(presenter.finish().unwrap()).unwrap(),
+            format!("\x1b[2J\x1b[?25l{frame}{frame}\x1b[0m\x1b[?25h")
+        );
+    }
+
+    #[test]
+    fn present_odd_height() {
+        let mut presenter = TerminalPresenter::new(vec![], Size::new(1, 1));
+        presenter
+            .present(&Canvas::from_pixels(array![[0x010203]]))
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(presenter.writer).unwrap(),
+            "\x1b[2J\x1b[?25l\x1b[H\x1b[38;2;1;2;3m\x1b[49m▀\x1b[0m\x1b[K\r\n"
+        );
+    }
+}